@@ -1,6 +1,4 @@
-use std::boxed::FnBox;
-
-pub struct BoxFn0<T>(Box<dyn FnBox() -> T>);
+pub struct BoxFn0<T>(Box<dyn FnOnce() -> T>);
 impl<T> BoxFn0<T> {
     pub fn new<F: 'static + FnOnce() -> T>(f: F) -> Self {
         BoxFn0(Box::new(f))
@@ -10,7 +8,7 @@ impl<T> BoxFn0<T> {
     }
 }
 
-pub struct BoxFn1<A0, T>(Box<dyn FnBox(A0) -> T>);
+pub struct BoxFn1<A0, T>(Box<dyn FnOnce(A0) -> T>);
 impl<A0, T> BoxFn1<A0, T> {
     pub fn new<F: 'static + FnOnce(A0) -> T>(f: F) -> Self {
         BoxFn1(Box::new(f))
@@ -20,7 +18,7 @@ impl<A0, T> BoxFn1<A0, T> {
     }
 }
 
-pub struct BoxFn2<A0, A1, T>(Box<dyn FnBox(A0, A1) -> T>);
+pub struct BoxFn2<A0, A1, T>(Box<dyn FnOnce(A0, A1) -> T>);
 impl<A0, A1, T> BoxFn2<A0, A1, T> {
     pub fn new<F: 'static + FnOnce(A0, A1) -> T>(f: F) -> Self {
         BoxFn2(Box::new(f))
@@ -30,7 +28,7 @@ impl<A0, A1, T> BoxFn2<A0, A1, T> {
     }
 }
 
-pub struct BoxFn3<A0, A1, A2, T>(Box<dyn FnBox(A0, A1, A2) -> T>);
+pub struct BoxFn3<A0, A1, A2, T>(Box<dyn FnOnce(A0, A1, A2) -> T>);
 impl<A0, A1, A2, T> BoxFn3<A0, A1, A2, T> {
     pub fn new<F: 'static + FnOnce(A0, A1, A2) -> T>(f: F) -> Self {
         BoxFn3(Box::new(f))
@@ -40,7 +38,7 @@ impl<A0, A1, A2, T> BoxFn3<A0, A1, A2, T> {
     }
 }
 
-pub struct BoxFn4<A0, A1, A2, A3, T>(Box<dyn FnBox(A0, A1, A2, A3) -> T>);
+pub struct BoxFn4<A0, A1, A2, A3, T>(Box<dyn FnOnce(A0, A1, A2, A3) -> T>);
 impl<A0, A1, A2, A3, T> BoxFn4<A0, A1, A2, A3, T> {
     pub fn new<F: 'static + FnOnce(A0, A1, A2, A3) -> T>(f: F) -> Self {
         BoxFn4(Box::new(f))
@@ -0,0 +1,160 @@
+//! Storage for values passed to mocked methods, e.g. callbacks
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::MatchArg;
+
+/// Slot for value passed to mocked method, usually callback which test
+/// wants to invoke later:
+///
+/// ```rust,ignore
+/// #[mocked]
+/// pub trait Events {
+///     fn on_event(&self, cb: Box<dyn FnMut(u32)>);
+/// }
+///
+/// #[test]
+/// fn test_callback() {
+///     let scenario = Scenario::new();
+///     let mock = scenario.create_mock::<EventsMock>();
+///
+///     let on_event = Captured::new();
+///     scenario.expect(mock.on_event_call(ANY).and_call(on_event.setter()));
+///
+///     mock.on_event(Box::new(|e| println!("{}", e)));
+///     on_event.with(|cb| cb(2));
+/// }
+/// ```
+///
+/// Clones refer to the same slot.
+pub struct Captured<T> {
+    value: Rc<RefCell<Option<T>>>,
+}
+
+impl<T> Captured<T> {
+    pub fn new() -> Self {
+        Captured {
+            value: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// Stores value, replacing previously captured one.
+    pub fn set(&self, value: T) {
+        *self.value.borrow_mut() = Some(value);
+    }
+
+    /// Returns closure storing its argument, suitable for `and_call`
+    /// reaction of single-argument method.
+    pub fn setter(&self) -> impl Fn(T) {
+        let captured = self.clone();
+        move |value| captured.set(value)
+    }
+
+    /// Returns whether value is captured.
+    pub fn is_captured(&self) -> bool {
+        self.value.borrow().is_some()
+    }
+
+    /// Calls `f` with mutable reference to captured value, e.g. to invoke
+    /// captured `FnMut` callback. Panics if nothing is captured.
+    ///
+    /// Captured value is borrowed during call, so `f` must not capture
+    /// new value to the same slot.
+    pub fn with<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        let mut value = self.value.borrow_mut();
+        f(value.as_mut().expect("no value is captured"))
+    }
+
+    /// Takes captured value out of slot, e.g. to invoke captured `FnOnce`
+    /// callback. Panics if nothing is captured.
+    pub fn take(&self) -> T {
+        self.value.borrow_mut().take().expect("no value is captured")
+    }
+}
+
+impl<T> Clone for Captured<T> {
+    fn clone(&self) -> Self {
+        Captured {
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<T> Default for Captured<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Storage for all values of some method argument, for inspecting them
+/// after calls are made:
+///
+/// ```rust,ignore
+/// let requests = ArgCaptor::new();
+/// scenario.expect(mock.send_call(requests.capture()).and_return(()));
+///
+/// client.sync();
+/// assert_eq!(requests.values()[0].path, "/items");
+/// ```
+///
+/// Value is recorded each time matcher is checked, so it is better used
+/// on expectations which other arguments don't narrow.
+/// Clones refer to the same storage.
+pub struct ArgCaptor<T> {
+    values: Rc<RefCell<Vec<T>>>,
+}
+
+impl<T: Clone> ArgCaptor<T> {
+    pub fn new() -> Self {
+        ArgCaptor {
+            values: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Returns matcher which matches any value and records it.
+    pub fn capture(&self) -> CaptureMatchArg<T> {
+        CaptureMatchArg(self.clone())
+    }
+
+    /// Returns all captured values in order of capturing.
+    pub fn values(&self) -> Vec<T> {
+        self.values.borrow().clone()
+    }
+
+    /// Returns last captured value. Panics if nothing is captured.
+    pub fn last(&self) -> T {
+        self.values
+            .borrow()
+            .last()
+            .cloned()
+            .expect("no value is captured")
+    }
+}
+
+impl<T> Clone for ArgCaptor<T> {
+    fn clone(&self) -> Self {
+        ArgCaptor {
+            values: self.values.clone(),
+        }
+    }
+}
+
+impl<T: Clone> Default for ArgCaptor<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Matcher returned by `ArgCaptor::capture`.
+pub struct CaptureMatchArg<T>(ArgCaptor<T>);
+impl<T: Clone> MatchArg<T> for CaptureMatchArg<T> {
+    fn matches(&self, arg: &T) -> Result<(), String> {
+        self.0.values.borrow_mut().push(arg.clone());
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        "capture()".to_owned()
+    }
+}
@@ -62,13 +62,13 @@
 macro_rules! mock_clone {
     ($mock_name:ident) => {
         #[cfg(test)]
-        impl Clone for $mock_name {
+        impl ::std::clone::Clone for $mock_name {
             fn clone(&self) -> Self {
                 let method_data = ::mockers::MethodData {
                     mock_id: self.mock_id,
                     mock_type_id: 0usize,
                     method_name: "clone",
-                    type_param_ids: vec![],
+                    type_param_ids: ::std::vec![],
                 };
                 let action = self.scenario.borrow_mut().verify0(method_data);
                 action.call()
@@ -78,17 +78,17 @@ macro_rules! mock_clone {
         impl $mock_name {
             #[allow(dead_code)]
             pub fn clone_call(&self) -> ::mockers::CallMatch0<Self> {
-                ::mockers::CallMatch0::new(self.mock_id, 0usize, "clone", vec![])
+                ::mockers::CallMatch0::new(self.mock_id, 0usize, "clone", ::std::vec![])
             }
         }
     };
 
     ($mock_name:ident, share_expectations) => {
         #[cfg(test)]
-        impl Clone for $mock_name {
+        impl ::std::clone::Clone for $mock_name {
             fn clone(&self) -> Self {
                 use $crate::Mock;
-                $mock_name::new(self.mock_id, self.scenario.clone())
+                $mock_name::new(self.mock_id, ::std::clone::Clone::clone(&self.scenario))
             }
         }
     };
@@ -0,0 +1,19 @@
+trait MaybeDefault: Sized {
+    fn maybe_default() -> Option<Self>;
+}
+impl<T> MaybeDefault for T {
+    default fn maybe_default() -> Option<T> {
+        None
+    }
+}
+impl<T: Default> MaybeDefault for T {
+    fn maybe_default() -> Option<T> {
+        Some(T::default())
+    }
+}
+
+/// Returns default value of `T` if it implements `Default`, `None` otherwise.
+/// This function is used by code generated by mocking macros.
+pub fn maybe_default<T>() -> Option<T> {
+    T::maybe_default()
+}
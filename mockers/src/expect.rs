@@ -0,0 +1,96 @@
+//! Fluent API for setting expectations:
+//! `mock.expect_foo().with(2, ANY).returning(|a, b| a + b)`.
+//!
+//! It is alternative to `scenario.expect(mock.foo_call(2, ANY).and_call(|a, b| a + b))`
+//! for those who are used to mocking libraries in other languages.
+//! `expect_*` methods are generated alongside `*_call` ones.
+
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::{CallMatch0, CallMatch1, CallMatch2, CallMatch3, CallMatch4};
+use crate::{MatchArg, ScenarioInternals};
+
+macro_rules! define_expect {
+    ($expect:ident, $expect_with:ident, $call_match:ident,
+     $($arg_name:ident: $arg:ident => $matcher:ident),*) => {
+        /// Expectation for method call, which arguments aren't given yet.
+        /// Returned by generated `expect_*` mock methods.
+        #[must_use]
+        pub struct $expect<$($arg,)* Res> {
+            scenario: Rc<RefCell<ScenarioInternals>>,
+            mock_id: usize,
+            mock_type_id: usize,
+            method_name: &'static str,
+            type_param_ids: Vec<crate::TypeParamId>,
+            _phantom: PhantomData<fn() -> ($($arg,)* Res,)>,
+        }
+        impl<$($arg,)* Res> $expect<$($arg,)* Res> {
+            pub fn new(
+                scenario: Rc<RefCell<ScenarioInternals>>,
+                mock_id: usize,
+                mock_type_id: usize,
+                method_name: &'static str,
+                type_param_ids: Vec<crate::TypeParamId>,
+            ) -> Self {
+                $expect {
+                    scenario,
+                    mock_id,
+                    mock_type_id,
+                    method_name,
+                    type_param_ids,
+                    _phantom: PhantomData,
+                }
+            }
+
+            /// Sets argument matchers, the same as ones passed to `*_call` method.
+            pub fn with<$($matcher: MatchArg<$arg> + 'static),*>(
+                self,
+                $($arg_name: $matcher),*
+            ) -> $expect_with<$($arg,)* Res> {
+                $expect_with {
+                    scenario: self.scenario,
+                    call_match: $call_match::new(
+                        self.mock_id,
+                        self.mock_type_id,
+                        self.method_name,
+                        self.type_param_ids,
+                        $(Box::new($arg_name)),*
+                    ),
+                }
+            }
+        }
+
+        /// Expectation for method call with given argument matchers.
+        #[must_use]
+        pub struct $expect_with<$($arg,)* Res> {
+            scenario: Rc<RefCell<ScenarioInternals>>,
+            call_match: $call_match<$($arg,)* Res>,
+        }
+        impl<$($arg: 'static,)* Res: 'static> $expect_with<$($arg,)* Res> {
+            /// Adds expectation to scenario, matched call is handled by given
+            /// function. Equivalent of `scenario.expect(foo_call(...).and_call(func))`.
+            pub fn returning<F>(self, func: F)
+            where
+                F: FnOnce($($arg),*) -> Res + 'static,
+            {
+                let expectation = self.call_match.and_call(func);
+                self.scenario
+                    .borrow_mut()
+                    .expectations
+                    .push(Box::new(expectation));
+            }
+        }
+    };
+}
+
+define_expect!(Expect0, ExpectWith0, CallMatch0,);
+define_expect!(Expect1, ExpectWith1, CallMatch1, arg0: Arg0 => Arg0Match);
+define_expect!(Expect2, ExpectWith2, CallMatch2,
+               arg0: Arg0 => Arg0Match, arg1: Arg1 => Arg1Match);
+define_expect!(Expect3, ExpectWith3, CallMatch3,
+               arg0: Arg0 => Arg0Match, arg1: Arg1 => Arg1Match, arg2: Arg2 => Arg2Match);
+define_expect!(Expect4, ExpectWith4, CallMatch4,
+               arg0: Arg0 => Arg0Match, arg1: Arg1 => Arg1Match, arg2: Arg2 => Arg2Match,
+               arg3: Arg3 => Arg3Match);
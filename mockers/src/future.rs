@@ -0,0 +1,34 @@
+//! Helpers for methods returning boxed futures, like manually written
+//! async trait methods:
+//!
+//! ```rust,ignore
+//! #[mocked]
+//! pub trait Store {
+//!     fn fetch(&self, id: u32) -> Pin<Box<dyn Future<Output = String> + Send>>;
+//! }
+//!
+//! scenario.expect(mock.fetch_call(1).and_return(boxed_ready("item".to_owned())));
+//! ```
+//!
+//! Unlike `async fn` and `impl Future` methods, which are mocked as if they
+//! return future output, such methods return future itself, so it must be
+//! built by action.
+
+use std::future::{ready, Future};
+use std::pin::Pin;
+
+/// Boxed future which may be sent to other threads.
+pub type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// Boxed future which can't be sent to other threads.
+pub type LocalBoxFuture<T> = Pin<Box<dyn Future<Output = T>>>;
+
+/// Returns `Send` boxed future which is immediately ready with given value.
+pub fn boxed_ready<T: Send + 'static>(value: T) -> BoxFuture<T> {
+    Box::pin(ready(value))
+}
+
+/// Returns boxed future which is immediately ready with given value.
+pub fn local_boxed_ready<T: 'static>(value: T) -> LocalBoxFuture<T> {
+    Box::pin(ready(value))
+}
@@ -1,4 +1,4 @@
-#![feature(fnbox, specialization)]
+#![feature(specialization, unboxed_closures, tuple_trait)]
 
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
@@ -13,20 +13,123 @@ mod colors;
 mod box_fn;
 pub mod cardinality;
 mod dbg;
+mod default;
+pub mod expect;
+pub mod future;
 pub mod matchers;
+pub mod capture;
 #[macro_use]
 pub mod clone;
 pub mod type_info;
 
-pub use crate::type_info::TypeInfo;
+pub use crate::capture::{ArgCaptor, Captured};
+pub use crate::default::maybe_default;
+pub use crate::expect::{Expect0, Expect1, Expect2, Expect3, Expect4};
+pub use crate::type_info::{TypeInfo, TypeParamId};
 
 use crate::cardinality::{Cardinality, CardinalityCheckResult};
 use crate::dbg::dbg;
 
+/// Registered mock: its ID, name of mock type (used for detecting mock type
+/// ID collisions) and scenario it belongs to.
+type ExternMockEntry = (usize, &'static str, Weak<RefCell<ScenarioInternals>>);
+
 thread_local! {
     // Mapping from mock_type_id of 'extern' block mock to corresponding mock object.
     // It is needed since mock is object but mocked functions are static.
-    pub static EXTERN_MOCKS: RefCell<HashMap<usize, (usize, Rc<RefCell<ScenarioInternals>>)>> = RefCell::new(HashMap::new());
+    // Scenario is referenced weakly, so that registration left by mock which
+    // outlived its scenario (e.g. was leaked) doesn't affect following tests.
+    pub static EXTERN_MOCKS: RefCell<HashMap<usize, ExternMockEntry>> = RefCell::new(HashMap::new());
+}
+
+/// Returns mock type ID derived from unique name of mock type, so that ID
+/// doesn't depend on macro expansion order and is the same across builds.
+/// Name is hashed with 64-bit FNV-1a, which, unlike `DefaultHasher`, is stable.
+/// Hash may still collide (and is truncated on 32-bit targets), so mocks
+/// registered by ID are checked for collisions by `register_extern_mock`.
+/// This function is used by code generated by mocking macros.
+pub const fn mock_type_id(name: &str) -> usize {
+    let bytes = name.as_bytes();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        i += 1;
+    }
+    hash as usize
+}
+
+/// Registers mock for extern block or static methods, so that stubs may find it.
+/// Returns `false` if there is already registered mock of the same type whose
+/// scenario is still alive. Registrations of destroyed scenarios are replaced.
+/// Panics if mock of another type with the same ID is registered.
+/// This function is used by code generated by mocking macros.
+pub fn register_extern_mock(
+    mock_type_id: usize,
+    mock_type_name: &'static str,
+    mock_id: usize,
+    scenario_int: &Rc<RefCell<ScenarioInternals>>,
+) -> bool {
+    EXTERN_MOCKS.with(|mocks| {
+        let mut mocks = mocks.borrow_mut();
+        if let Some((_, registered_name, scenario)) = mocks.get(&mock_type_id) {
+            if *registered_name != mock_type_name {
+                panic!(
+                    "mock types `{}` and `{}` have the same ID, rename one of them",
+                    registered_name, mock_type_name
+                );
+            }
+            if scenario.upgrade().is_some() {
+                return false;
+            }
+        }
+        mocks.insert(mock_type_id, (mock_id, mock_type_name, Rc::downgrade(scenario_int)));
+        true
+    })
+}
+
+/// Removes registration made by `register_extern_mock` for given mock.
+/// Registration of another mock of the same type is left intact.
+/// This function is used by code generated by mocking macros.
+pub fn unregister_extern_mock(
+    mock_type_id: usize,
+    mock_id: usize,
+    scenario_int: &Rc<RefCell<ScenarioInternals>>,
+) {
+    EXTERN_MOCKS.with(|mocks| {
+        let mut mocks = mocks.borrow_mut();
+        let is_registered = match mocks.get(&mock_type_id) {
+            Some(&(id, _, ref scenario)) => {
+                id == mock_id && Weak::ptr_eq(scenario, &Rc::downgrade(scenario_int))
+            }
+            None => false,
+        };
+        if is_registered {
+            mocks.remove(&mock_type_id);
+        }
+    });
+}
+
+/// Removes registrations of all mocks belonging to scenario, so that mocks
+/// which outlive it (e.g. were leaked) don't block creating new ones.
+fn unregister_scenario_mocks(scenario_int: &Rc<RefCell<ScenarioInternals>>) {
+    let scenario_weak = Rc::downgrade(scenario_int);
+    EXTERN_MOCKS.with(|mocks| {
+        mocks
+            .borrow_mut()
+            .retain(|_, &mut (_, _, ref scenario)| !Weak::ptr_eq(scenario, &scenario_weak));
+    });
+}
+
+/// Returns ID and scenario of registered mock of given type.
+/// This function is used by code generated by mocking macros.
+pub fn get_extern_mock(mock_type_id: usize) -> (usize, Rc<RefCell<ScenarioInternals>>) {
+    EXTERN_MOCKS.with(|mocks| {
+        let mocks = mocks.borrow();
+        let &(mock_id, _, ref scenario) = mocks.get(&mock_type_id).expect("Mock instance not found");
+        (mock_id, scenario.upgrade().expect("scenario is dead"))
+    })
 }
 
 type Action0<T> = box_fn::BoxFn0<T>;
@@ -65,7 +168,7 @@ pub trait CallMatch {
     fn get_mock_id(&self) -> usize;
     fn get_mock_type_id(&self) -> usize;
     fn get_method_name(&self) -> &'static str;
-    fn get_type_param_ids(&self) -> &[usize];
+    fn get_type_param_ids(&self) -> &[TypeParamId];
     fn describe(&self) -> String;
 }
 
@@ -74,6 +177,36 @@ pub trait Expectation {
     fn is_satisfied(&self) -> bool;
     fn satisfy(&mut self, call: Call, mock_name: &str) -> box_fn::BoxFn0<*mut u8>;
     fn describe(&self) -> String;
+
+    /// Describes expected number of calls, like `called exactly 2 times`.
+    fn expected_calls(&self) -> String {
+        "called exactly one time".to_string()
+    }
+    /// Returns number of calls matched so far.
+    fn calls_count(&self) -> u32 {
+        0
+    }
+
+    /// Returns `Some` for sequence, so that expectations it consists of
+    /// may be inspected separately.
+    fn as_sequence(&self) -> Option<&Sequence> {
+        None
+    }
+    fn as_sequence_mut(&mut self) -> Option<&mut Sequence> {
+        None
+    }
+
+    /// Creates sequence of this expectation followed by `next` one,
+    /// so `a.then(b).then(c)` expects calls in given order.
+    fn then<E: Expectation + 'static>(self, next: E) -> Sequence
+    where
+        Self: Sized + 'static,
+    {
+        let mut seq = Sequence::new();
+        seq.expect(self);
+        seq.expect(next);
+        seq
+    }
 }
 
 pub struct ExpectationNever<CM: CallMatch> {
@@ -103,11 +236,11 @@ pub struct CallMatch0<Res> {
     mock_id: usize,
     mock_type_id: usize,
     method_name: &'static str,
-    type_param_ids: Vec<usize>,
+    type_param_ids: Vec<TypeParamId>,
     _phantom: PhantomData<Res>,
 }
 impl<Res> CallMatch0<Res> {
-    pub fn new(mock_id: usize, mock_type_id: usize, method_name: &'static str, type_param_ids: Vec<usize>) -> Self {
+    pub fn new(mock_id: usize, mock_type_id: usize, method_name: &'static str, type_param_ids: Vec<TypeParamId>) -> Self {
         CallMatch0 {
             mock_id: mock_id,
             mock_type_id: mock_type_id,
@@ -142,7 +275,7 @@ impl<Res> CallMatch for CallMatch0<Res> {
     fn get_method_name(&self) -> &'static str {
         self.method_name
     }
-    fn get_type_param_ids(&self) -> &[usize] {
+    fn get_type_param_ids(&self) -> &[TypeParamId] {
         &self.type_param_ids
     }
     fn describe(&self) -> String {
@@ -217,6 +350,12 @@ impl<Res: 'static> Expectation for ExpectationTimes0<Res> {
             self.count
         )
     }
+    fn expected_calls(&self) -> String {
+        self.cardinality.describe()
+    }
+    fn calls_count(&self) -> u32 {
+        self.count
+    }
 }
 
 #[must_use]
@@ -301,6 +440,17 @@ impl<Res> CallMatch0<Res> {
             action: Rc::new(RefCell::new(func)),
         }
     }
+
+    /// Expects call as many times as there are given results, results are
+    /// returned in order, one per call.
+    pub fn and_return_sequence(self, results: Vec<Res>) -> ExpectationTimes0<Res>
+    where
+        Res: 'static,
+    {
+        let count = results.len() as u32;
+        let mut results = results.into_iter();
+        self.and_call_clone(move || results.next().unwrap()).times(count)
+    }
 }
 
 impl<Res: Default + 'static> CallMatch0<Res> {
@@ -310,6 +460,12 @@ impl<Res: Default + 'static> CallMatch0<Res> {
             action: Rc::new(RefCell::new(Res::default)),
         }
     }
+
+    /// Expects call given number of times, default value is returned.
+    /// Shortcut for `and_return_default().times(cardinality)`.
+    pub fn times<C: Cardinality + 'static>(self, cardinality: C) -> ExpectationTimes0<Res> {
+        self.and_return_default().times(cardinality)
+    }
 }
 
 #[must_use]
@@ -317,7 +473,7 @@ pub struct CallMatch1<Arg0, Res> {
     mock_id: usize,
     mock_type_id: usize,
     method_name: &'static str,
-    type_param_ids: Vec<usize>,
+    type_param_ids: Vec<TypeParamId>,
     arg0: Box<dyn MatchArg<Arg0>>,
 
     _phantom: PhantomData<Res>,
@@ -327,7 +483,7 @@ impl<Arg0, Res> CallMatch1<Arg0, Res> {
         mock_id: usize,
         mock_type_id: usize,
         method_name: &'static str,
-        type_param_ids: Vec<usize>,
+        type_param_ids: Vec<TypeParamId>,
         arg0: Box<dyn MatchArg<Arg0>>,
     ) -> Self {
         CallMatch1 {
@@ -373,7 +529,7 @@ impl<Arg0, Res> CallMatch for CallMatch1<Arg0, Res> {
     fn get_method_name(&self) -> &'static str {
         self.method_name
     }
-    fn get_type_param_ids(&self) -> &[usize] {
+    fn get_type_param_ids(&self) -> &[TypeParamId] {
         &self.type_param_ids
     }
     fn describe(&self) -> String {
@@ -449,6 +605,12 @@ impl<Arg0: 'static, Res: 'static> Expectation for ExpectationTimes1<Arg0, Res> {
             self.count
         )
     }
+    fn expected_calls(&self) -> String {
+        self.cardinality.describe()
+    }
+    fn calls_count(&self) -> u32 {
+        self.count
+    }
 }
 
 #[must_use]
@@ -533,6 +695,14 @@ impl<Arg0, Res: 'static> CallMatch1<Arg0, Res> {
             action: Rc::new(RefCell::new(func)),
         }
     }
+
+    /// Expects call as many times as there are given results, results are
+    /// returned in order, one per call.
+    pub fn and_return_sequence(self, results: Vec<Res>) -> ExpectationTimes1<Arg0, Res> {
+        let count = results.len() as u32;
+        let mut results = results.into_iter();
+        self.and_call_clone(move |_| results.next().unwrap()).times(count)
+    }
 }
 impl<Arg0, Res: Default + 'static> CallMatch1<Arg0, Res> {
     pub fn and_return_default(self) -> Reaction1<Arg0, Res> {
@@ -541,6 +711,12 @@ impl<Arg0, Res: Default + 'static> CallMatch1<Arg0, Res> {
             action: Rc::new(RefCell::new(|_| Res::default())),
         }
     }
+
+    /// Expects call given number of times, default value is returned.
+    /// Shortcut for `and_return_default().times(cardinality)`.
+    pub fn times<C: Cardinality + 'static>(self, cardinality: C) -> ExpectationTimes1<Arg0, Res> {
+        self.and_return_default().times(cardinality)
+    }
 }
 
 #[must_use]
@@ -548,7 +724,7 @@ pub struct CallMatch2<Arg0, Arg1, Res> {
     mock_id: usize,
     mock_type_id: usize,
     method_name: &'static str,
-    type_param_ids: Vec<usize>,
+    type_param_ids: Vec<TypeParamId>,
     arg0: Box<dyn MatchArg<Arg0>>,
     arg1: Box<dyn MatchArg<Arg1>>,
 
@@ -559,7 +735,7 @@ impl<Arg0, Arg1, Res> CallMatch2<Arg0, Arg1, Res> {
         mock_id: usize,
         mock_type_id: usize,
         method_name: &'static str,
-        type_param_ids: Vec<usize>,
+        type_param_ids: Vec<TypeParamId>,
         arg0: Box<dyn MatchArg<Arg0>>,
         arg1: Box<dyn MatchArg<Arg1>>,
     ) -> Self {
@@ -606,7 +782,7 @@ impl<Arg0, Arg1, Res> CallMatch for CallMatch2<Arg0, Arg1, Res> {
     fn get_method_name(&self) -> &'static str {
         self.method_name
     }
-    fn get_type_param_ids(&self) -> &[usize] {
+    fn get_type_param_ids(&self) -> &[TypeParamId] {
         &self.type_param_ids
     }
     fn describe(&self) -> String {
@@ -692,6 +868,12 @@ impl<Arg0: 'static, Arg1: 'static, Res: 'static> Expectation
             self.count
         )
     }
+    fn expected_calls(&self) -> String {
+        self.cardinality.describe()
+    }
+    fn calls_count(&self) -> u32 {
+        self.count
+    }
 }
 
 #[must_use]
@@ -776,6 +958,14 @@ impl<Arg0, Arg1, Res: 'static> CallMatch2<Arg0, Arg1, Res> {
             action: Rc::new(RefCell::new(func)),
         }
     }
+
+    /// Expects call as many times as there are given results, results are
+    /// returned in order, one per call.
+    pub fn and_return_sequence(self, results: Vec<Res>) -> ExpectationTimes2<Arg0, Arg1, Res> {
+        let count = results.len() as u32;
+        let mut results = results.into_iter();
+        self.and_call_clone(move |_, _| results.next().unwrap()).times(count)
+    }
 }
 impl<Arg0, Arg1, Res: Default + 'static> CallMatch2<Arg0, Arg1, Res> {
     pub fn and_return_default(self) -> Reaction2<Arg0, Arg1, Res> {
@@ -784,6 +974,15 @@ impl<Arg0, Arg1, Res: Default + 'static> CallMatch2<Arg0, Arg1, Res> {
             action: Rc::new(RefCell::new(|_, _| Res::default())),
         }
     }
+
+    /// Expects call given number of times, default value is returned.
+    /// Shortcut for `and_return_default().times(cardinality)`.
+    pub fn times<C: Cardinality + 'static>(
+        self,
+        cardinality: C,
+    ) -> ExpectationTimes2<Arg0, Arg1, Res> {
+        self.and_return_default().times(cardinality)
+    }
 }
 
 #[must_use]
@@ -791,7 +990,7 @@ pub struct CallMatch3<Arg0, Arg1, Arg2, Res> {
     mock_id: usize,
     mock_type_id: usize,
     method_name: &'static str,
-    type_param_ids: Vec<usize>,
+    type_param_ids: Vec<TypeParamId>,
     arg0: Box<dyn MatchArg<Arg0>>,
     arg1: Box<dyn MatchArg<Arg1>>,
     arg2: Box<dyn MatchArg<Arg2>>,
@@ -803,7 +1002,7 @@ impl<Arg0, Arg1, Arg2, Res> CallMatch3<Arg0, Arg1, Arg2, Res> {
         mock_id: usize,
         mock_type_id: usize,
         method_name: &'static str,
-        type_param_ids: Vec<usize>,
+        type_param_ids: Vec<TypeParamId>,
         arg0: Box<dyn MatchArg<Arg0>>,
         arg1: Box<dyn MatchArg<Arg1>>,
         arg2: Box<dyn MatchArg<Arg2>>,
@@ -858,7 +1057,7 @@ impl<Arg0, Arg1, Arg2, Res> CallMatch for CallMatch3<Arg0, Arg1, Arg2, Res> {
     fn get_method_name(&self) -> &'static str {
         self.method_name
     }
-    fn get_type_param_ids(&self) -> &[usize] {
+    fn get_type_param_ids(&self) -> &[TypeParamId] {
         &self.type_param_ids
     }
     fn describe(&self) -> String {
@@ -945,6 +1144,12 @@ impl<Arg0: 'static, Arg1: 'static, Arg2: 'static, Res: 'static> Expectation
             self.count
         )
     }
+    fn expected_calls(&self) -> String {
+        self.cardinality.describe()
+    }
+    fn calls_count(&self) -> u32 {
+        self.count
+    }
 }
 
 #[must_use]
@@ -1031,6 +1236,14 @@ impl<Arg0, Arg1, Arg2, Res: 'static> CallMatch3<Arg0, Arg1, Arg2, Res> {
             action: Rc::new(RefCell::new(func)),
         }
     }
+
+    /// Expects call as many times as there are given results, results are
+    /// returned in order, one per call.
+    pub fn and_return_sequence(self, results: Vec<Res>) -> ExpectationTimes3<Arg0, Arg1, Arg2, Res> {
+        let count = results.len() as u32;
+        let mut results = results.into_iter();
+        self.and_call_clone(move |_, _, _| results.next().unwrap()).times(count)
+    }
 }
 impl<Arg0, Arg1, Arg2, Res: Default + 'static> CallMatch3<Arg0, Arg1, Arg2, Res> {
     pub fn and_return_default(self) -> Reaction3<Arg0, Arg1, Arg2, Res> {
@@ -1039,6 +1252,15 @@ impl<Arg0, Arg1, Arg2, Res: Default + 'static> CallMatch3<Arg0, Arg1, Arg2, Res>
             action: Rc::new(RefCell::new(move |_, _, _| Res::default())),
         }
     }
+
+    /// Expects call given number of times, default value is returned.
+    /// Shortcut for `and_return_default().times(cardinality)`.
+    pub fn times<C: Cardinality + 'static>(
+        self,
+        cardinality: C,
+    ) -> ExpectationTimes3<Arg0, Arg1, Arg2, Res> {
+        self.and_return_default().times(cardinality)
+    }
 }
 
 #[must_use]
@@ -1046,7 +1268,7 @@ pub struct CallMatch4<Arg0, Arg1, Arg2, Arg3, Res> {
     mock_id: usize,
     mock_type_id: usize,
     method_name: &'static str,
-    type_param_ids: Vec<usize>,
+    type_param_ids: Vec<TypeParamId>,
     arg0: Box<dyn MatchArg<Arg0>>,
     arg1: Box<dyn MatchArg<Arg1>>,
     arg2: Box<dyn MatchArg<Arg2>>,
@@ -1059,7 +1281,7 @@ impl<Arg0, Arg1, Arg2, Arg3, Res> CallMatch4<Arg0, Arg1, Arg2, Arg3, Res> {
         mock_id: usize,
         mock_type_id: usize,
         method_name: &'static str,
-        type_param_ids: Vec<usize>,
+        type_param_ids: Vec<TypeParamId>,
         arg0: Box<dyn MatchArg<Arg0>>,
         arg1: Box<dyn MatchArg<Arg1>>,
         arg2: Box<dyn MatchArg<Arg2>>,
@@ -1118,7 +1340,7 @@ impl<Arg0, Arg1, Arg2, Arg3, Res> CallMatch for CallMatch4<Arg0, Arg1, Arg2, Arg
     fn get_method_name(&self) -> &'static str {
         self.method_name
     }
-    fn get_type_param_ids(&self) -> &[usize] {
+    fn get_type_param_ids(&self) -> &[TypeParamId] {
         &self.type_param_ids
     }
     fn describe(&self) -> String {
@@ -1206,6 +1428,12 @@ impl<Arg0: 'static, Arg1: 'static, Arg2: 'static, Arg3: 'static, Res: 'static> E
             self.count
         )
     }
+    fn expected_calls(&self) -> String {
+        self.cardinality.describe()
+    }
+    fn calls_count(&self) -> u32 {
+        self.count
+    }
 }
 
 #[must_use]
@@ -1293,6 +1521,14 @@ impl<Arg0, Arg1, Arg2, Arg3, Res: 'static> CallMatch4<Arg0, Arg1, Arg2, Arg3, Re
             action: Rc::new(RefCell::new(func)),
         }
     }
+
+    /// Expects call as many times as there are given results, results are
+    /// returned in order, one per call.
+    pub fn and_return_sequence(self, results: Vec<Res>) -> ExpectationTimes4<Arg0, Arg1, Arg2, Arg3, Res> {
+        let count = results.len() as u32;
+        let mut results = results.into_iter();
+        self.and_call_clone(move |_, _, _, _| results.next().unwrap()).times(count)
+    }
 }
 impl<Arg0, Arg1, Arg2, Arg3, Res: Default + 'static> CallMatch4<Arg0, Arg1, Arg2, Arg3, Res> {
     pub fn and_return_default(self) -> Reaction4<Arg0, Arg1, Arg2, Arg3, Res> {
@@ -1301,6 +1537,15 @@ impl<Arg0, Arg1, Arg2, Arg3, Res: Default + 'static> CallMatch4<Arg0, Arg1, Arg2
             action: Rc::new(RefCell::new(|_, _, _, _| Res::default())),
         }
     }
+
+    /// Expects call given number of times, default value is returned.
+    /// Shortcut for `and_return_default().times(cardinality)`.
+    pub fn times<C: Cardinality + 'static>(
+        self,
+        cardinality: C,
+    ) -> ExpectationTimes4<Arg0, Arg1, Arg2, Arg3, Res> {
+        self.and_return_default().times(cardinality)
+    }
 }
 
 /// Argument matcher
@@ -1314,7 +1559,8 @@ pub trait MatchArg<T> {
 }
 
 /// Matches argument with value of same type using equality.
-impl<T: Eq + std::fmt::Debug> MatchArg<T> for T {
+/// `PartialEq` is enough, so floats can be matched directly too.
+impl<T: PartialEq + std::fmt::Debug> MatchArg<T> for T {
     fn matches(&self, arg: &T) -> Result<(), String> {
         if self == arg {
             Ok(())
@@ -1328,6 +1574,121 @@ impl<T: Eq + std::fmt::Debug> MatchArg<T> for T {
     }
 }
 
+/// Conversion into argument matcher.
+///
+/// Generated `*_call` methods accept it instead of `MatchArg` for arguments
+/// of `&T` type, where `T` is primitive type, `String` or `str`, so that such
+/// arguments may be matched by owned values, e.g. `foo_call(2u32)` for
+/// `fn foo(&self, a: &u32)`. Any matcher converts into itself.
+pub trait IntoMatchArg<T> {
+    fn into_match_arg(self) -> Box<dyn MatchArg<T>>;
+}
+
+impl<T, M: MatchArg<T> + 'static> IntoMatchArg<T> for M {
+    fn into_match_arg(self) -> Box<dyn MatchArg<T>> {
+        Box::new(self)
+    }
+}
+
+/// Matches reference argument with owned value using equality.
+struct RefEqMatchArg<T>(T);
+
+impl<'a, T: PartialEq<U> + std::fmt::Debug, U: ?Sized + std::fmt::Debug> MatchArg<&'a U>
+    for RefEqMatchArg<T>
+{
+    fn matches(&self, arg: &&'a U) -> Result<(), String> {
+        if self.0 == **arg {
+            Ok(())
+        } else {
+            Err(format!("{:?} is not equal to {:?}", arg, self.0))
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+macro_rules! impl_into_match_arg_by_value {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a> IntoMatchArg<&'a $ty> for $ty {
+                fn into_match_arg(self) -> Box<dyn MatchArg<&'a $ty>> {
+                    Box::new(RefEqMatchArg(self))
+                }
+            }
+        )*
+    }
+}
+
+impl_into_match_arg_by_value!(
+    bool, char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, String
+);
+
+impl<'a> IntoMatchArg<&'a str> for String {
+    fn into_match_arg(self) -> Box<dyn MatchArg<&'a str>> {
+        Box::new(RefEqMatchArg(self))
+    }
+}
+
+/// Arguments of `impl Display` type are converted to `String`, generated
+/// `*_call` methods accept string literals for them.
+impl IntoMatchArg<String> for &'static str {
+    fn into_match_arg(self) -> Box<dyn MatchArg<String>> {
+        Box::new(self.to_owned())
+    }
+}
+
+/// Reactions available for all `CallMatchN` types, `Args` is tuple of
+/// method argument types. With `opaque_call_match` option generated `*_call`
+/// methods return `impl CallMatchReactions<..>` instead of concrete `CallMatchN`,
+/// so that tests don't depend on call matcher types.
+pub trait CallMatchReactions<Args: std::marker::Tuple, Res>: CallMatch + Sized {
+    type Expectation: Expectation;
+
+    fn and_return(self, result: Res) -> Self::Expectation;
+    fn and_panic(self, msg: String) -> Self::Expectation;
+    fn and_call<F>(self, func: F) -> Self::Expectation
+    where
+        F: FnOnce<Args, Output = Res> + 'static;
+    fn never(self) -> ExpectationNever<Self>;
+}
+
+macro_rules! impl_call_match_reactions {
+    ($call_match:ident, $expectation:ident, $($arg:ident),*) => {
+        impl<$($arg: 'static,)* Res: 'static> CallMatchReactions<($($arg,)*), Res>
+            for $call_match<$($arg,)* Res>
+        {
+            type Expectation = $expectation<$($arg,)* Res>;
+
+            fn and_return(self, result: Res) -> Self::Expectation {
+                $call_match::and_return(self, result)
+            }
+            fn and_panic(self, msg: String) -> Self::Expectation {
+                $call_match::and_panic(self, msg)
+            }
+            fn and_call<F>(self, func: F) -> Self::Expectation
+            where
+                F: FnOnce<($($arg,)*), Output = Res> + 'static,
+            {
+                $call_match::and_call(self, func)
+            }
+            fn never(self) -> ExpectationNever<Self> {
+                $call_match::never(self)
+            }
+        }
+    };
+}
+
+impl_call_match_reactions!(CallMatch0, Expectation0,);
+impl_call_match_reactions!(CallMatch1, Expectation1, Arg0);
+impl_call_match_reactions!(CallMatch2, Expectation2, Arg0, Arg1);
+impl_call_match_reactions!(CallMatch3, Expectation3, Arg0, Arg1, Arg2);
+impl_call_match_reactions!(CallMatch4, Expectation4, Arg0, Arg1, Arg2, Arg3);
+
+/// Expectations which must be satisfied in given order. Only the first
+/// not yet satisfied expectation is matched against calls, so expectations
+/// may belong to different mocks, then calls to all of them are ordered.
 #[derive(Default)]
 pub struct Sequence {
     expectations: Vec<Box<dyn Expectation>>,
@@ -1343,11 +1704,24 @@ impl Sequence {
         assert!(!expectation.is_satisfied());
         self.expectations.push(Box::new(expectation));
     }
+
+    /// Adds expectation to the end of sequence, allows chaining
+    /// expectations like `a.then(b).then(c)`.
+    pub fn then<E: Expectation + 'static>(mut self, expectation: E) -> Self {
+        self.expect(expectation);
+        self
+    }
 }
 impl Expectation for Sequence {
     fn call_match(&self) -> &dyn CallMatch {
         self.expectations[0].call_match()
     }
+    fn as_sequence(&self) -> Option<&Sequence> {
+        Some(self)
+    }
+    fn as_sequence_mut(&mut self) -> Option<&mut Sequence> {
+        Some(self)
+    }
     fn is_satisfied(&self) -> bool {
         self.expectations.is_empty()
     }
@@ -1367,6 +1741,38 @@ impl Expectation for Sequence {
     fn describe(&self) -> String {
         self.expectations[0].describe()
     }
+    fn expected_calls(&self) -> String {
+        self.expectations[0].expected_calls()
+    }
+    fn calls_count(&self) -> u32 {
+        self.expectations[0].calls_count()
+    }
+}
+
+/// Returns given expectation or, for sequence, all expectations remaining
+/// in it, so that each of them is attributed to its own mock.
+fn leaf_expectations(expectation: &dyn Expectation) -> Vec<&dyn Expectation> {
+    match expectation.as_sequence() {
+        Some(seq) => seq
+            .expectations
+            .iter()
+            .flat_map(|e| leaf_expectations(e.as_ref()))
+            .collect(),
+        None => vec![expectation],
+    }
+}
+
+/// Removes expectations for given mock, including ones in sequence.
+/// Returns whether anything is left of expectation.
+fn remove_mock_expectations(expectation: &mut dyn Expectation, mock_id: usize) -> bool {
+    match expectation.as_sequence_mut() {
+        Some(seq) => {
+            seq.expectations
+                .retain_mut(|e| remove_mock_expectations(e.as_mut(), mock_id));
+            !seq.expectations.is_empty()
+        }
+        None => expectation.call_match().get_mock_id() != mock_id,
+    }
 }
 
 pub trait Mock {
@@ -1374,10 +1780,81 @@ pub trait Mock {
     fn mocked_class_name() -> &'static str;
 }
 
+#[diagnostic::on_unimplemented(
+    message = "mock for `{Self}` can't be created with `create_mock_for`",
+    label = "`create_mock_for` isn't available for this trait",
+    note = "`create_mock_for` isn't available when trait isn't mocked with `#[mocked]`, \
+            can't be made into object or mock is less visible than trait, \
+            use `create_mock::<TraitMock>()` instead"
+)]
 pub trait Mocked {
     type MockImpl: Mock;
 }
 
+/// Expectation which isn't satisfied yet, returned by
+/// `Scenario::unmet_expectations` for building custom reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnmetExpectation {
+    /// Name of mock object, like `A#0`.
+    pub mock_name: String,
+    pub method_name: &'static str,
+    /// Description of expected call, like `foo(2)`.
+    pub call: String,
+    /// Expected number of calls, like `called exactly 2 times`.
+    pub expected_calls: String,
+    /// Number of calls matched so far.
+    pub calls_count: u32,
+}
+
+/// Decision of handler set with `Scenario::on_unexpected_call`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnexpectedCallAction {
+    /// Panic with error message, like without handler.
+    Panic,
+    /// Return default value from mocked method and continue. Call still
+    /// panics if result type doesn't implement `Default`.
+    ReturnDefault,
+}
+
+type UnexpectedCallHandler = Rc<dyn Fn(&str) -> UnexpectedCallAction>;
+
+/// Unexpected call report, which is handled once scenario borrow is
+/// released, so that handler may use scenario.
+pub struct UnexpectedCall {
+    msg: String,
+    handler: Option<UnexpectedCallHandler>,
+}
+
+impl UnexpectedCall {
+    #[track_caller]
+    fn handle<Res>(self) -> Res {
+        if let Some(handler) = self.handler {
+            if handler(&self.msg) == UnexpectedCallAction::ReturnDefault {
+                if let Some(result) = maybe_default::<Res>() {
+                    return result;
+                }
+            }
+        }
+        panic!("{}", self.msg);
+    }
+}
+
+/// Result of call verification returned by `verifyN` methods: either
+/// action of matched expectation or unexpected call report.
+/// This type is used by code generated by mocking macros.
+pub struct Reaction<Res>(Result<box_fn::BoxFn0<Res>, UnexpectedCall>);
+
+impl<Res> Reaction<Res> {
+    /// Performs action of matched expectation and returns its result.
+    #[track_caller]
+    pub fn call(self) -> Res {
+        match self.0 {
+            Ok(action) => action.call(),
+            Err(unexpected) => unexpected.handle(),
+        }
+    }
+}
+
 pub struct ScenarioInternals {
     expectations: Vec<Box<dyn Expectation>>,
 
@@ -1387,6 +1864,9 @@ pub struct ScenarioInternals {
     mock_names: HashMap<usize, Rc<String>>,
     /// Set of used mock names used to quicly check for conflicts.
     allocated_names: HashSet<Rc<String>>,
+
+    /// Handler called with error message when unexpected call occurs.
+    unexpected_call_handler: Option<UnexpectedCallHandler>,
 }
 
 impl ScenarioInternals {
@@ -1451,6 +1931,34 @@ impl ScenarioInternals {
         self.mock_names.insert(mock_id, name_rc.clone());
         self.allocated_names.insert(name_rc);
     }
+
+    /// Returns expectations which aren't satisfied yet. Sequences are
+    /// reported as expectations remaining in them.
+    pub fn unmet_expectations(&self) -> Vec<UnmetExpectation> {
+        self.leaf_expectations()
+            .filter(|e| !e.is_satisfied())
+            .map(|e| self.describe_unmet(e))
+            .collect()
+    }
+
+    /// Returns expectations for given mock which aren't satisfied yet.
+    pub fn unmet_expectations_for(&self, mock_id: usize) -> Vec<UnmetExpectation> {
+        self.leaf_expectations()
+            .filter(|e| !e.is_satisfied() && e.call_match().get_mock_id() == mock_id)
+            .map(|e| self.describe_unmet(e))
+            .collect()
+    }
+
+    fn describe_unmet(&self, expectation: &dyn Expectation) -> UnmetExpectation {
+        let call_match = expectation.call_match();
+        UnmetExpectation {
+            mock_name: self.mock_names[&call_match.get_mock_id()].to_string(),
+            method_name: call_match.get_method_name(),
+            call: call_match.describe(),
+            expected_calls: expectation.expected_calls(),
+            calls_count: expectation.calls_count(),
+        }
+    }
 }
 
 pub struct Scenario {
@@ -1466,6 +1974,8 @@ impl Scenario {
 
                 mock_names: HashMap::new(),
                 allocated_names: HashSet::new(),
+
+                unexpected_call_handler: None,
             })),
         }
     }
@@ -1504,6 +2014,23 @@ impl Scenario {
         self.internals.borrow_mut().expectations.clear();
     }
 
+    /// Sets handler which is called with error message when mock receives
+    /// unexpected call. Handler may record the message, or abort process,
+    /// e.g. in setups where unwinding isn't available, and then decide
+    /// whether call panics as usual or returns default value.
+    ///
+    /// Handler is called when scenario isn't borrowed, so it may use
+    /// scenario, e.g. to inspect unmet expectations.
+    pub fn on_unexpected_call<F: Fn(&str) -> UnexpectedCallAction + 'static>(&self, handler: F) {
+        self.internals.borrow_mut().unexpected_call_handler = Some(Rc::new(handler));
+    }
+
+    /// Returns expectations which aren't satisfied yet, so that custom
+    /// reports may be built instead of panic message.
+    pub fn unmet_expectations(&self) -> Vec<UnmetExpectation> {
+        self.internals.borrow().unmet_expectations()
+    }
+
     pub fn handle(&self) -> ScenarioHandle {
         ScenarioHandle::new(Rc::downgrade(&self.internals))
     }
@@ -1534,6 +2061,8 @@ impl Default for Scenario {
 
 impl Drop for Scenario {
     fn drop(&mut self) {
+        unregister_scenario_mocks(&self.internals);
+
         // Test is already failed, so it isn't necessary to check remaining
         // expectations. And if we do, then panic-during-drop will cause
         // test to fail with uncomprehensive message like:
@@ -1546,6 +2075,15 @@ impl Drop for Scenario {
     }
 }
 
+/// Creates mock which isn't attached to any scenario, so no expectations may
+/// be set for it and any call panics. It is useful for tests which need mock
+/// instance only to satisfy type checks, e.g. to pass it where it is never
+/// used. Don't use it for mocks of extern blocks and static methods, they are
+/// registered globally.
+pub fn detached_mock<T: Mock>() -> T {
+    Scenario::new().create_mock::<T>()
+}
+
 pub struct ScenarioHandle {
     internals: Weak<RefCell<ScenarioInternals>>,
 }
@@ -1589,6 +2127,38 @@ impl ScenarioHandle {
     }
 }
 
+/// Mock handle which doesn't keep scenario alive, returned by `weak`
+/// method of mocks generated with `clone` option.
+pub struct WeakMock<T: Mock> {
+    mock_id: usize,
+    internals: Weak<RefCell<ScenarioInternals>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Mock> WeakMock<T> {
+    pub fn new(mock_id: usize, scenario_int: Weak<RefCell<ScenarioInternals>>) -> Self {
+        Self {
+            mock_id,
+            internals: scenario_int,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns mock sharing expectations with original one or `None`
+    /// if scenario is already destroyed.
+    pub fn upgrade(&self) -> Option<T> {
+        self.internals
+            .upgrade()
+            .map(|scenario_int| T::new(self.mock_id, scenario_int))
+    }
+}
+
+impl<T: Mock> Clone for WeakMock<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.mock_id, self.internals.clone())
+    }
+}
+
 pub struct Call {
     pub method_data: MethodData,
     pub args_ptr: *const u8,
@@ -1619,11 +2189,12 @@ pub struct MethodData {
     pub method_name: &'static str,
 
     /// Type parameters of generic method
-    pub type_param_ids: Vec<usize>,
+    pub type_param_ids: Vec<TypeParamId>,
 }
 
 impl ScenarioInternals {
-    pub fn verify0<Res>(&mut self, method_data: MethodData) -> box_fn::BoxFn0<Res> {
+    #[track_caller]
+    pub fn verify0<Res>(&mut self, method_data: MethodData) -> Reaction<Res> {
         let args = Box::new(());
         let args_ptr: *const u8 = ::std::boxed::Box::into_raw(args) as *const u8;
         fn destroy(args_to_destroy: *const u8) {
@@ -1639,15 +2210,17 @@ impl ScenarioInternals {
             destroy: destroy,
             format_args: format_args,
         };
-        let action = self.verify(call);
-        box_fn::BoxFn0::new(move || {
-            let result_ptr: *mut u8 = action.call();
-            let result: Box<Res> = unsafe { Box::from_raw(result_ptr as *mut Res) };
-            *result
-        })
+        Reaction(self.verify(call).map(|action| {
+            box_fn::BoxFn0::new(move || {
+                let result_ptr: *mut u8 = action.call();
+                let result: Box<Res> = unsafe { Box::from_raw(result_ptr as *mut Res) };
+                *result
+            })
+        }))
     }
 
-    pub fn verify1<A0, Res>(&mut self, method_data: MethodData, a0: A0) -> box_fn::BoxFn0<Res> {
+    #[track_caller]
+    pub fn verify1<A0, Res>(&mut self, method_data: MethodData, a0: A0) -> Reaction<Res> {
         let args = Box::new((a0,));
         let args_ptr: *const u8 = ::std::boxed::Box::into_raw(args) as *const u8;
         fn destroy<A0>(args_to_destroy: *const u8) {
@@ -1663,20 +2236,22 @@ impl ScenarioInternals {
             destroy: destroy::<A0>,
             format_args: format_args::<A0>,
         };
-        let action = self.verify(call);
-        box_fn::BoxFn0::new(move || {
-            let result_ptr: *mut u8 = action.call();
-            let result: Box<Res> = unsafe { Box::from_raw(result_ptr as *mut Res) };
-            *result
-        })
+        Reaction(self.verify(call).map(|action| {
+            box_fn::BoxFn0::new(move || {
+                let result_ptr: *mut u8 = action.call();
+                let result: Box<Res> = unsafe { Box::from_raw(result_ptr as *mut Res) };
+                *result
+            })
+        }))
     }
 
+    #[track_caller]
     pub fn verify2<A0, A1, Res>(
         &mut self,
         method_data: MethodData,
         a0: A0,
         a1: A1,
-    ) -> box_fn::BoxFn0<Res> {
+    ) -> Reaction<Res> {
         let args = Box::new((a0, a1));
         let args_ptr: *const u8 = ::std::boxed::Box::into_raw(args) as *const u8;
         fn destroy<A0, A1>(args_to_destroy: *const u8) {
@@ -1692,21 +2267,23 @@ impl ScenarioInternals {
             destroy: destroy::<A0, A1>,
             format_args: format_args::<A0, A1>,
         };
-        let action = self.verify(call);
-        box_fn::BoxFn0::new(move || {
-            let result_ptr: *mut u8 = action.call();
-            let result: Box<Res> = unsafe { Box::from_raw(result_ptr as *mut Res) };
-            *result
-        })
+        Reaction(self.verify(call).map(|action| {
+            box_fn::BoxFn0::new(move || {
+                let result_ptr: *mut u8 = action.call();
+                let result: Box<Res> = unsafe { Box::from_raw(result_ptr as *mut Res) };
+                *result
+            })
+        }))
     }
 
+    #[track_caller]
     pub fn verify3<A0, A1, A2, Res>(
         &mut self,
         method_data: MethodData,
         a0: A0,
         a1: A1,
         a2: A2,
-    ) -> box_fn::BoxFn0<Res> {
+    ) -> Reaction<Res> {
         let args = Box::new((a0, a1, a2));
         let args_ptr: *const u8 = ::std::boxed::Box::into_raw(args) as *const u8;
         fn destroy<A0, A1, A2>(args_to_destroy: *const u8) {
@@ -1727,14 +2304,16 @@ impl ScenarioInternals {
             destroy: destroy::<A0, A1, A2>,
             format_args: format_args::<A0, A1, A2>,
         };
-        let action = self.verify(call);
-        box_fn::BoxFn0::new(move || {
-            let result_ptr: *mut u8 = action.call();
-            let result: Box<Res> = unsafe { Box::from_raw(result_ptr as *mut Res) };
-            *result
-        })
+        Reaction(self.verify(call).map(|action| {
+            box_fn::BoxFn0::new(move || {
+                let result_ptr: *mut u8 = action.call();
+                let result: Box<Res> = unsafe { Box::from_raw(result_ptr as *mut Res) };
+                *result
+            })
+        }))
     }
 
+    #[track_caller]
     pub fn verify4<A0, A1, A2, A3, Res>(
         &mut self,
         method_data: MethodData,
@@ -1742,7 +2321,7 @@ impl ScenarioInternals {
         a1: A1,
         a2: A2,
         a3: A3,
-    ) -> box_fn::BoxFn0<Res> {
+    ) -> Reaction<Res> {
         let args = Box::new((a0, a1, a2, a3));
         let args_ptr: *const u8 = ::std::boxed::Box::into_raw(args) as *const u8;
         fn destroy<A0, A1, A2, A3>(args_to_destroy: *const u8) {
@@ -1764,21 +2343,26 @@ impl ScenarioInternals {
             destroy: destroy::<A0, A1, A2, A3>,
             format_args: format_args::<A0, A1, A2, A3>,
         };
-        let action = self.verify(call);
-        box_fn::BoxFn0::new(move || {
-            let result_ptr: *mut u8 = action.call();
-            let result: Box<Res> = unsafe { Box::from_raw(result_ptr as *mut Res) };
-            *result
-        })
+        Reaction(self.verify(call).map(|action| {
+            box_fn::BoxFn0::new(move || {
+                let result_ptr: *mut u8 = action.call();
+                let result: Box<Res> = unsafe { Box::from_raw(result_ptr as *mut Res) };
+                *result
+            })
+        }))
     }
 
     /// Verify call performed on mock object
-    /// Returns closure which returns result upon call.
+    /// Returns closure which returns result upon call, or unexpected call
+    /// report.
     /// Closure returned instead of actual result, because expectation may
     /// use user-provided closure as action, and that closure may want to
     /// use scenario object to create mocks or establish expectations, so
     /// we need to release scenario borrow before calling expectation action.
-    fn verify(&mut self, call: Call) -> box_fn::BoxFn0<*mut u8> {
+    /// Panics are reported at location of mocked method call, because
+    /// generated stubs track caller too.
+    #[track_caller]
+    fn verify(&mut self, call: Call) -> Result<box_fn::BoxFn0<*mut u8>, UnexpectedCall> {
         for expectation in self.expectations.iter_mut().rev() {
             if expectation.call_match().matches(&call) {
                 let mock_name = self
@@ -1786,7 +2370,7 @@ impl ScenarioInternals {
                     .get(&call.method_data.mock_id)
                     .unwrap()
                     .clone();
-                return expectation.satisfy(call, &mock_name);
+                return Ok(expectation.satisfy(call, &mock_name));
             }
         }
 
@@ -1812,7 +2396,7 @@ impl ScenarioInternals {
 
         if self.expectations.is_empty() {
             msg.push_str("no call are expected");
-            panic!(msg);
+            return Err(self.unexpected_call(msg));
         }
 
         let mut target_first_match = true;
@@ -1892,7 +2476,50 @@ impl ScenarioInternals {
         }
 
         msg.push('\n');
-        panic!(msg);
+        Err(self.unexpected_call(msg))
+    }
+
+    fn unexpected_call(&self, msg: String) -> UnexpectedCall {
+        UnexpectedCall {
+            msg,
+            handler: self.unexpected_call_handler.clone(),
+        }
+    }
+
+    /// Checks whether there are any expectations for given method of given
+    /// mock object, regardless of arguments. Expectations which are
+    /// waiting for their turn in sequence are taken into account too.
+    /// This method is used by code generated by mocking macros.
+    pub fn has_expectations_for(&self, method_data: &MethodData) -> bool {
+        self.leaf_expectations().any(|expectation| {
+            let call_match = expectation.call_match();
+            call_match.get_mock_id() == method_data.mock_id
+                && call_match.get_mock_type_id() == method_data.mock_type_id
+                && call_match.get_method_name() == method_data.method_name
+                && call_match.get_type_param_ids() == &method_data.type_param_ids[..]
+        })
+    }
+
+    fn leaf_expectations(&self) -> impl Iterator<Item = &dyn Expectation> {
+        self.expectations
+            .iter()
+            .flat_map(|e| leaf_expectations(e.as_ref()))
+    }
+
+    /// Checks whether all expectations for given mock object are satisfied.
+    /// This method is used by code generated by mocking macros.
+    pub fn expectations_satisfied_for(&self, mock_id: usize) -> bool {
+        self.leaf_expectations()
+            .filter(|expectation| expectation.call_match().get_mock_id() == mock_id)
+            .all(|expectation| expectation.is_satisfied())
+    }
+
+    /// Removes all expectations for given mock object, satisfied or not.
+    /// Calls of other mocks in the same sequences stay ordered.
+    /// This method is used by code generated by mocking macros.
+    pub fn reset_expectations_for(&mut self, mock_id: usize) {
+        self.expectations
+            .retain_mut(|expectation| remove_mock_expectations(expectation.as_mut(), mock_id));
     }
 
     pub fn get_mock_name(&self, mock_id: usize) -> &str {
@@ -45,6 +45,9 @@ impl<T> MatchArg<T> for MatchAnyT<T> {
     }
 }
 
+/// Matches any value of type `T`. Unlike `ANY`, it is usable where matcher
+/// type must be known, e.g. inside `all_of!`. `T` is inferred from `*_call`
+/// method parameter type, so `mock.foo_call(any())` needs no annotations.
 pub fn any<T>() -> MatchAnyT<T> {
     MatchAnyT(PhantomData)
 }
@@ -95,7 +98,7 @@ macro_rules! simple_matcher {
                 }
 
                 fn describe(&self) -> String {
-                    format!("lt({:?})", self.0)
+                    format!(concat!(stringify!($func_name), "({:?})"), self.0)
                 }
             }
             pub fn $func_name<T: $($bounds)+ + Debug>(than: T) -> $class_name<T> {
@@ -182,7 +185,7 @@ impl<T: Debug, M: MatchArg<T>> MatchArg<T> for NotMatchArg<T, M> {
     }
 
     fn describe(&self) -> String {
-        format!("lt({:?})", self.0.describe())
+        format!("not({})", self.0.describe())
     }
 }
 pub fn not<T: Debug, M: MatchArg<T>>(matcher: M) -> NotMatchArg<T, M> {
@@ -235,6 +238,116 @@ pub fn or<T: Debug, M0: MatchArg<T>, M1: MatchArg<T>>(
     OrMatchArg(matcher0, matcher1, PhantomData)
 }
 
+pub struct AllOfMatchArg<T>(Vec<Box<dyn MatchArg<T>>>);
+impl<T> MatchArg<T> for AllOfMatchArg<T> {
+    fn matches(&self, arg: &T) -> Result<(), String> {
+        for matcher in &self.0 {
+            if let Err(err) = matcher.matches(arg) {
+                return Err(format!("{} (failed {})", err, matcher.describe()));
+            }
+        }
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        let descriptions: Vec<String> = self.0.iter().map(|m| m.describe()).collect();
+        format!("all_of({})", descriptions.join(", "))
+    }
+}
+/// Matches argument satisfying all given matchers, checked in order until first
+/// failed one. Use `all_of!` macro to combine matchers of different types.
+pub fn all_of<T>(matchers: Vec<Box<dyn MatchArg<T>>>) -> AllOfMatchArg<T> {
+    AllOfMatchArg(matchers)
+}
+
+/// Boxes matcher for combinator macros. Unlike cast to `Box<dyn MatchArg<_>>`,
+/// it lets compiler infer type of literals like `gt(2)` from argument type.
+#[doc(hidden)]
+pub fn boxed<T, M: MatchArg<T> + 'static>(matcher: M) -> Box<dyn MatchArg<T>> {
+    Box::new(matcher)
+}
+
+pub struct AnyOfMatchArg<T>(Vec<Box<dyn MatchArg<T>>>);
+impl<T> MatchArg<T> for AnyOfMatchArg<T> {
+    fn matches(&self, arg: &T) -> Result<(), String> {
+        let mut errors = Vec::new();
+        for matcher in &self.0 {
+            match matcher.matches(arg) {
+                Ok(()) => return Ok(()),
+                Err(err) => errors.push(format!("{} (failed {})", err, matcher.describe())),
+            }
+        }
+        Err(errors.join(" neither "))
+    }
+
+    fn describe(&self) -> String {
+        let descriptions: Vec<String> = self.0.iter().map(|m| m.describe()).collect();
+        format!("any_of({})", descriptions.join(", "))
+    }
+}
+/// Matches argument satisfying any of given matchers, checked in order until
+/// first successful one. Use `any_of!` macro to combine matchers of different types.
+pub fn any_of<T>(matchers: Vec<Box<dyn MatchArg<T>>>) -> AnyOfMatchArg<T> {
+    AnyOfMatchArg(matchers)
+}
+
+pub struct ElementsMatchArg<T>(Vec<Box<dyn MatchArg<T>>>);
+impl<T> ElementsMatchArg<T> {
+    fn matches_elements(&self, elements: &[T]) -> Result<(), String> {
+        if elements.len() != self.0.len() {
+            return Err(format!(
+                "has {} elements, but {} are expected",
+                elements.len(),
+                self.0.len()
+            ));
+        }
+        for (index, (matcher, element)) in self.0.iter().zip(elements).enumerate() {
+            if let Err(err) = matcher.matches(element) {
+                return Err(format!("element #{}: {}", index, err));
+            }
+        }
+        Ok(())
+    }
+
+    fn describe_elements(&self) -> String {
+        let descriptions: Vec<String> = self.0.iter().map(|m| m.describe()).collect();
+        format!("[{}]", descriptions.join(", "))
+    }
+}
+impl<'a, T> MatchArg<&'a [T]> for ElementsMatchArg<T> {
+    fn matches(&self, arg: &&'a [T]) -> Result<(), String> {
+        self.matches_elements(arg)
+    }
+
+    fn describe(&self) -> String {
+        self.describe_elements()
+    }
+}
+impl<'a, T> MatchArg<&'a mut [T]> for ElementsMatchArg<T> {
+    fn matches(&self, arg: &&'a mut [T]) -> Result<(), String> {
+        self.matches_elements(arg)
+    }
+
+    fn describe(&self) -> String {
+        self.describe_elements()
+    }
+}
+impl<T> MatchArg<Vec<T>> for ElementsMatchArg<T> {
+    fn matches(&self, arg: &Vec<T>) -> Result<(), String> {
+        self.matches_elements(arg)
+    }
+
+    fn describe(&self) -> String {
+        self.describe_elements()
+    }
+}
+/// Matches slice or vector having exactly one element for each given matcher,
+/// each element is matched by corresponding matcher. Use `elements!` macro
+/// to combine matchers of different types.
+pub fn elements<T>(matchers: Vec<Box<dyn MatchArg<T>>>) -> ElementsMatchArg<T> {
+    ElementsMatchArg(matchers)
+}
+
 pub struct FnMatchArg<T, F: Fn(&T) -> Result<(), String>> {
     func: F,
     _phantom: PhantomData<T>,
@@ -257,6 +370,12 @@ impl<T, F: Fn(&T) -> Result<(), String>> MatchArg<T> for FnMatchArg<T, F> {
     }
 }
 
+/// Matches argument using function which returns `Err` with mismatch
+/// explanation when argument doesn't match.
+pub fn matches_fn<T, F: Fn(&T) -> Result<(), String>>(f: F) -> FnMatchArg<T, F> {
+    FnMatchArg::new(f)
+}
+
 pub struct BoolFnMatchArg<T, F: Fn(&T) -> bool> {
     func: F,
     _phantom: PhantomData<T>,
@@ -314,3 +433,24 @@ macro_rules! check {
             .with_description_fn(move || format!("check!({})", lambda_str))
     }};
 }
+
+#[macro_export]
+macro_rules! all_of {
+    ($($m:expr),* $(,)*) => {
+        $crate::matchers::all_of(vec![$($crate::matchers::boxed($m)),*])
+    };
+}
+
+#[macro_export]
+macro_rules! any_of {
+    ($($m:expr),* $(,)*) => {
+        $crate::matchers::any_of(vec![$($crate::matchers::boxed($m)),*])
+    };
+}
+
+#[macro_export]
+macro_rules! elements {
+    ($($m:expr),* $(,)*) => {
+        $crate::matchers::elements(vec![$($crate::matchers::boxed($m)),*])
+    };
+}
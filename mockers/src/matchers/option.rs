@@ -1,16 +1,28 @@
 use std::marker::PhantomData;
 
 use super::super::MatchArg;
-use std::fmt::Debug;
+use crate::dbg::dbg;
 
-// Strictly speaking, this matcher is not needed, as you
-// may just use `None`, but it is added for symmetry.
-pub fn none<T>() -> Option<T> {
-    None
+/// Unlike plain `None` value, this matcher doesn't require option
+/// value type to implement `PartialEq` and `Debug`.
+pub struct MatchNone<T>(PhantomData<T>);
+impl<T> MatchArg<Option<T>> for MatchNone<T> {
+    fn matches(&self, option: &Option<T>) -> Result<(), String> {
+        match *option {
+            Some(..) => Err(format!("{:?} is not equal to None", dbg(option))),
+            None => Ok(()),
+        }
+    }
+    fn describe(&self) -> String {
+        "none()".to_owned()
+    }
+}
+pub fn none<T>() -> MatchNone<T> {
+    MatchNone(PhantomData)
 }
 
 pub struct MatchSome<T, M: MatchArg<T>>(M, PhantomData<T>);
-impl<T: Debug, M: MatchArg<T>> MatchArg<Option<T>> for MatchSome<T, M> {
+impl<T, M: MatchArg<T>> MatchArg<Option<T>> for MatchSome<T, M> {
     fn matches(&self, option: &Option<T>) -> Result<(), String> {
         match *option {
             Some(ref value) => self.0.matches(value),
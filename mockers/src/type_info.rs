@@ -1,10 +1,35 @@
 ///! Manual runtime type info for mocking generic methods
 
+use std::any::TypeId;
+
 pub trait TypeInfo {
     fn get_type_id() -> usize;
     fn get_type_name() -> &'static str;
 }
 
+/// ID of type parameter of mocked generic method. IDs of registered types
+/// and of `'static` types are kept apart, so they never collide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TypeParamId {
+    /// ID of type registered with `register_types!`.
+    Registered(usize),
+    /// `TypeId` of `'static` type.
+    Static(TypeId),
+}
+
+/// Returns ID of `'static` type, so that such types may be used as type
+/// parameters of mocked generic methods without registering them.
+/// This method is used by code generated by mocking macros.
+pub fn static_type_id<T: ?Sized + 'static>() -> TypeParamId {
+    TypeParamId::Static(TypeId::of::<T>())
+}
+
+/// Returns ID of type registered with `register_types!`.
+/// This method is used by code generated by mocking macros.
+pub fn registered_type_id<T: TypeInfo>() -> TypeParamId {
+    TypeParamId::Registered(T::get_type_id())
+}
+
 /// Panics with message explaining that types used as type parameters of mocked generic
 /// methods must be registered using `register_types` macro.
 /// This method is used by code generated by mocking macros.
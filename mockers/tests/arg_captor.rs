@@ -0,0 +1,53 @@
+///! Test that arguments may be captured for later assertions.
+use mockers::matchers::ANY;
+use mockers::{ArgCaptor, Scenario};
+use mockers_derive::mocked;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub path: String,
+    pub retries: u32,
+}
+
+#[mocked]
+pub trait Client {
+    fn send(&self, request: Request);
+    fn send_to(&self, host: u32, request: Request);
+}
+
+#[test]
+fn test_capture_args() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<ClientMock>();
+
+    let requests = ArgCaptor::new();
+    scenario.expect(mock.send_call(requests.capture()).and_return_default().times(2));
+
+    mock.send(Request { path: "/a".to_owned(), retries: 1 });
+    mock.send(Request { path: "/b".to_owned(), retries: 2 });
+
+    let paths: Vec<String> = requests.values().into_iter().map(|r| r.path).collect();
+    assert_eq!(paths, vec!["/a", "/b"]);
+    assert_eq!(requests.last().retries, 2);
+}
+
+#[test]
+fn test_capture_one_of_args() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<ClientMock>();
+
+    let requests = ArgCaptor::new();
+    scenario.expect(mock.send_to_call(ANY, requests.capture()).and_return(()));
+
+    let request = Request { path: "/c".to_owned(), retries: 0 };
+    mock.send_to(1, request.clone());
+
+    assert_eq!(requests.values(), vec![request]);
+}
+
+#[test]
+#[should_panic(expected = "no value is captured")]
+fn test_nothing_captured() {
+    let requests: ArgCaptor<Request> = ArgCaptor::new();
+    requests.last();
+}
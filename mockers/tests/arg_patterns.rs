@@ -0,0 +1,48 @@
+///! Test that mockers can mock methods with destructuring argument patterns.
+use mockers::matchers::ANY;
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+// Patterns are allowed in trait methods with body only.
+#[mocked]
+pub trait A {
+    fn handle(&self, (x, y): (i32, i32)) -> i32 {
+        x * y
+    }
+    fn wild(&self, _: u32, b: u32);
+}
+
+#[test]
+fn test_tuple_pattern() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.handle_call((1, 2)).and_call(|(x, y)| x + y));
+
+    assert_eq!(mock.handle((1, 2)), 3);
+}
+
+#[test]
+fn test_wildcard_pattern() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.wild_call(ANY, 2).and_return_default().times(1));
+
+    mock.wild(1, 2);
+}
+
+#[mocked(delegate_defaults)]
+pub trait B {
+    fn sum(&self, (x, y): (i32, i32)) -> i32 {
+        x + y
+    }
+}
+
+#[test]
+fn test_pattern_in_default_method() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<BMock>();
+
+    assert_eq!(mock.sum((2, 3)), 5);
+}
@@ -23,6 +23,15 @@ fn test_assocated_type() {
     assert_eq!(mock.create(), 2);
 }
 
+/// Tests that trait object type with associated types may be given with `dyn`.
+#[test]
+fn test_assocated_type_dyn() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<dyn A<Item = i32>>();
+    scenario.expect(mock.create_call().and_return(2));
+    assert_eq!(mock.create(), 2);
+}
+
 /// Tests that all references to `Self` in trait definition are
 /// properly qualified with trait path in function signatures.
 #[test]
@@ -32,3 +41,77 @@ fn test_self_type_qualification() {
     scenario.expect(mock.create_call(1).and_return(vec![(true, 2)]));
     assert_eq!(mock.create(1), vec![(true, 2)]);
 }
+
+#[mocked(no_debug_bound)]
+pub trait C {
+    type Stream;
+    fn open(&self) -> Self::Stream;
+}
+
+/// Type which doesn't implement `Debug`.
+pub struct NotDebug(u32);
+
+/// Tests that `no_debug_bound` option allows associated types which
+/// don't implement `Debug`.
+#[test]
+fn test_associated_type_without_debug() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CMock<NotDebug>>();
+    scenario.expect(mock.open_call().and_call(|| NotDebug(3)));
+    assert_eq!(mock.open().0, 3);
+}
+
+#[mocked]
+pub trait D {
+    type Item: Clone + Send;
+    fn next(&self) -> Self::Item;
+}
+
+/// Tests that bounds of associated types are carried over to
+/// mock type parameters.
+#[test]
+fn test_associated_type_bounds() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<D<Item = String>>();
+    scenario.expect(mock.next_call().and_return_clone("a".to_owned()).times(2));
+    let item = mock.next();
+    assert_eq!(item.clone(), mock.next());
+}
+
+/// Sets expectation in generic context, it is only possible when matcher
+/// types refer to mock type parameter directly.
+fn expect_next<Item: Clone + Send + std::fmt::Debug + 'static>(
+    scenario: &Scenario,
+    mock: &DMock<Item>,
+    item: Item,
+) {
+    let call: mockers::CallMatch0<Item> = mock.next_call();
+    scenario.expect(call.and_return(item));
+}
+
+/// Tests that associated types in `*_call` methods resolve to mock type
+/// parameters.
+#[test]
+fn test_associated_type_is_mock_parameter() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<DMock<u32>>();
+    expect_next(&scenario, &mock, 5);
+    assert_eq!(mock.next(), 5);
+}
+
+#[mocked]
+pub trait E {
+    type Error;
+    fn run(&self) -> Result<(), Self::Error>;
+}
+
+/// Tests that associated type nested into result type of `*_call`
+/// resolves to mock type parameter.
+#[test]
+fn test_associated_type_in_result() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<EMock<String>>();
+    let call: mockers::CallMatch0<Result<(), String>> = mock.run_call();
+    scenario.expect(call.and_return(Err("failed".to_owned())));
+    assert_eq!(mock.run(), Err("failed".to_owned()));
+}
@@ -0,0 +1,76 @@
+///! Test that async methods and methods returning futures can be mocked.
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use mockers_derive::mocked;
+
+use mockers::future::{boxed_ready, local_boxed_ready};
+use mockers::Scenario;
+
+#[mocked]
+pub trait A {
+    fn foo(&self) -> impl Future<Output = u32>;
+    async fn bar(&self, a: u32) -> u32;
+}
+
+/// Hand-written async trait, methods return boxed futures.
+#[mocked]
+pub trait Store {
+    fn fetch(&self, id: u32) -> Pin<Box<dyn Future<Output = String> + Send>>;
+    fn count(&self) -> Pin<Box<dyn Future<Output = usize>>>;
+}
+
+/// Minimal executor, it is enough for futures returned by mocks
+/// which are always ready.
+fn block_on<F: Future>(future: F) -> F::Output {
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut context = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = Pin::as_mut(&mut future).poll(&mut context) {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn test_method_returning_future() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.foo_call().and_return(2));
+
+    assert_eq!(block_on(mock.foo()), 2);
+}
+
+#[test]
+fn test_async_method() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.bar_call(3).and_return(4));
+
+    assert_eq!(block_on(mock.bar(3)), 4);
+}
+
+#[test]
+fn test_method_returning_boxed_future() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<dyn Store>();
+
+    scenario.expect(mock.fetch_call(1).and_return(boxed_ready("item".to_owned())));
+    scenario.expect(mock.count_call().and_call(|| local_boxed_ready(3)));
+
+    assert_eq!(block_on(mock.fetch(1)), "item");
+    assert_eq!(block_on(mock.count()), 3);
+}
@@ -0,0 +1,42 @@
+///! Test that mocks may be returned as boxed trait objects from factory methods.
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Worker {
+    fn work(&self, job: u32) -> bool;
+}
+
+#[mocked]
+pub trait Factory {
+    fn build(&self) -> Box<dyn Worker>;
+}
+
+#[test]
+fn test_return_boxed_mock() {
+    let scenario = Scenario::new();
+    let factory = scenario.create_mock_for::<Factory>();
+    let worker = scenario.create_mock_for::<Worker>();
+
+    scenario.expect(worker.work_call(2).and_return(true));
+    scenario.expect(factory.build_call().and_return(Box::new(worker)));
+
+    assert!(factory.build().work(2));
+}
+
+#[test]
+fn test_create_boxed_mock_in_action() {
+    let scenario = Scenario::new();
+    let factory = scenario.create_mock_for::<Factory>();
+
+    scenario.expect(factory.build_call().and_call({
+        let scenario = scenario.handle();
+        move || {
+            let worker = scenario.create_mock_for::<Worker>();
+            scenario.expect(worker.work_call(3).and_return(false));
+            Box::new(worker)
+        }
+    }));
+
+    assert!(!factory.build().work(3));
+}
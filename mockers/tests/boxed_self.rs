@@ -0,0 +1,37 @@
+///! Test that methods with typed receivers like `self: Box<Self>` may be mocked.
+use std::pin::Pin;
+
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Task {
+    fn process(self: Box<Self>);
+    fn run(self: Box<Self>, input: u32) -> u32;
+    fn poll_step(self: Pin<&mut Self>) -> bool;
+}
+
+#[test]
+fn test_boxed_self() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<Task>();
+    scenario.expect(mock.process_call().and_return(()));
+    Box::new(mock).process();
+}
+
+#[test]
+fn test_boxed_self_trait_object() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<TaskMock>();
+    scenario.expect(mock.run_call(2).and_return(4));
+    let task: Box<dyn Task> = Box::new(mock);
+    assert_eq!(task.run(2), 4);
+}
+
+#[test]
+fn test_pinned_self() {
+    let scenario = Scenario::new();
+    let mut mock = scenario.create_mock::<TaskMock>();
+    scenario.expect(mock.poll_step_call().and_return(true));
+    assert!(Pin::new(&mut mock).poll_step());
+}
@@ -0,0 +1,55 @@
+///! Test that callbacks passed to mocked methods may be captured and invoked later.
+use std::cell::Cell;
+use std::rc::Rc;
+
+use mockers::matchers::ANY;
+use mockers::{Captured, Scenario};
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Events {
+    fn on_event(&self, cb: Box<dyn FnMut(u32)>);
+    fn on_done(&self, id: u32, cb: Box<dyn FnOnce(&str) -> bool>);
+}
+
+#[test]
+fn test_capture_fn_mut() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<EventsMock>();
+
+    let on_event = Captured::new();
+    scenario.expect(mock.on_event_call(ANY).and_call(on_event.setter()));
+
+    let sum = Rc::new(Cell::new(0));
+    let sum_ref = sum.clone();
+    mock.on_event(Box::new(move |e| sum_ref.set(sum_ref.get() + e)));
+
+    assert!(on_event.is_captured());
+    on_event.with(|cb| cb(2));
+    on_event.with(|cb| cb(3));
+    assert_eq!(sum.get(), 5);
+}
+
+#[test]
+fn test_capture_fn_once() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<EventsMock>();
+
+    let on_done = Captured::new();
+    let on_done_ref = on_done.clone();
+    scenario.expect(
+        mock.on_done_call(1, ANY)
+            .and_call(move |_, cb| on_done_ref.set(cb)),
+    );
+
+    mock.on_done(1, Box::new(|s| s == "ok"));
+    assert!(on_done.take()("ok"));
+    assert!(!on_done.is_captured());
+}
+
+#[test]
+#[should_panic(expected = "no value is captured")]
+fn test_nothing_captured() {
+    let on_event = Captured::<Box<dyn FnMut(u32)>>::new();
+    on_event.with(|cb| cb(2));
+}
@@ -19,6 +19,30 @@ fn test_times_exactly_satisfied() {
     mock.foo();
 }
 
+#[test]
+fn test_times_on_call_match() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.foo_call().times(2));
+
+    mock.foo();
+    mock.foo();
+}
+
+#[test]
+#[should_panic(expected = "Some expectations are not satisfied:
+`A#0.foo() must be called exactly 2 times, called 1 times`
+")]
+fn test_times_on_call_match_not_satisfied() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.foo_call().times(2));
+
+    mock.foo();
+}
+
 #[test]
 #[should_panic(expected = "Some expectations are not satisfied:
 `A#0.foo() must be called exactly 2 times, called 1 times`
@@ -0,0 +1,70 @@
+///! Test that conditionally compiled trait methods are mocked under the same conditions.
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait A {
+    fn foo(&self, a: u32);
+
+    #[cfg(test)]
+    fn enabled(&self) -> u32;
+
+    #[cfg(not(test))]
+    fn disabled(&self) -> u32;
+}
+
+#[test]
+fn test_cfg_enabled_method() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.enabled_call().and_return(2));
+    assert_eq!(mock.enabled(), 2);
+}
+
+#[mocked]
+pub trait B {
+    #[cfg(test)]
+    fn get(&self) -> u32;
+
+    #[cfg(not(test))]
+    fn get(&self) -> String;
+}
+
+#[test]
+fn test_cfg_mutually_exclusive_methods() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<BMock>();
+
+    scenario.expect(mock.get_call().and_return(3));
+    assert_eq!(mock.get(), 3);
+}
+
+#[cfg(test)]
+#[mocked]
+pub trait Enabled {
+    fn foo(&self) -> u32;
+}
+
+#[cfg(not(test))]
+#[mocked]
+pub trait Disabled {
+    fn foo(&self) -> u32;
+}
+
+#[test]
+fn test_cfg_enabled_trait() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<EnabledMock>();
+
+    scenario.expect(mock.foo_call().and_return(4));
+    assert_eq!(mock.foo(), 4);
+}
+
+// When `#[cfg]` goes after `#[mocked]`, the macro sees it on the trait
+// and must apply it to generated mock too.
+#[mocked]
+#[cfg(not(test))]
+pub trait DisabledAfter {
+    fn foo(&self) -> u32;
+}
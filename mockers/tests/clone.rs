@@ -2,7 +2,7 @@
 extern crate mockers;
 
 use mockers::Scenario;
-use mockers_derive::mock;
+use mockers_derive::{mock, mocked};
 
 pub trait A {
     fn foo(&self, a: u32);
@@ -72,3 +72,76 @@ fn test_clone_mock_dynamic() {
 
     target(mock);
 }
+
+#[mocked(clone)]
+pub trait B<T> {
+    fn bar(&self, a: T);
+}
+
+fn target_b<BC: B<u32> + Clone>(b: BC) {
+    let clone = b.clone();
+    clone.bar(2);
+}
+
+#[test]
+fn test_clone_option() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<BMock<u32>>();
+
+    scenario.expect(mock.bar_call(2).and_return_default().times(1));
+
+    target_b(mock);
+}
+
+#[test]
+fn test_weak_handle() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<BMock<u32>>();
+    let weak = mock.weak();
+
+    scenario.expect(mock.bar_call(2).and_return_default().times(1));
+
+    weak.upgrade().expect("scenario is alive").bar(2);
+}
+
+#[test]
+fn test_weak_handle_doesnt_keep_scenario_alive() {
+    let scenario = Scenario::new();
+    let weak = scenario.create_mock::<BMock<u32>>().weak();
+
+    drop(scenario);
+
+    assert!(weak.upgrade().is_none());
+}
+
+// `Debug` is implemented for mock anyway, so it is ignored in derive list.
+#[mocked(derive(Clone, Debug))]
+pub trait C {
+    fn baz(&self, a: u32);
+}
+
+#[test]
+fn test_derive_option() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CMock>();
+
+    scenario.expect(mock.baz_call(2).and_return_default().times(1));
+
+    mock.clone().baz(2);
+}
+
+/// `weak` isn't generated when trait has own method with the same name.
+#[mocked(clone)]
+pub trait D {
+    fn weak(&self) -> u32;
+}
+
+#[test]
+fn test_trait_method_named_weak() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<DMock>();
+
+    scenario.expect(mock.weak_call().and_return(3));
+
+    assert_eq!(mock.clone().weak(), 3);
+}
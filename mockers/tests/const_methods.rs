@@ -0,0 +1,27 @@
+///! Test that `const` qualifier of trait methods is dropped in mock.
+use mockers::Scenario;
+use mockers_derive::mock;
+
+pub trait Limits {
+    fn max_len(&self) -> usize;
+}
+
+// Trait definition given to `mock!` is used for mock generation only,
+// so it may contain `const` methods.
+mock! {
+    LimitsMock,
+    self,
+    trait Limits {
+        const fn max_len(&self) -> usize;
+    }
+}
+
+#[test]
+fn test_const_method() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<LimitsMock>();
+
+    scenario.expect(mock.max_len_call().and_return(10));
+
+    assert_eq!(mock.max_len(), 10);
+}
@@ -0,0 +1,67 @@
+///! Test that mock may call default implementations of trait methods.
+use mockers_derive::mocked;
+
+use mockers::Scenario;
+
+#[mocked(delegate_defaults)]
+pub trait A {
+    fn foo(&self) -> u32 {
+        1
+    }
+    fn bar(&self, a: u32) -> u32 {
+        self.foo() + a
+    }
+    fn baz(&self) -> u32;
+}
+
+/// Tests that default implementation is called when there are no expectations.
+#[test]
+fn test_default_implementation_is_called() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    assert_eq!(mock.bar(2), 3);
+}
+
+/// Tests that expectations take precedence over default implementation.
+#[test]
+fn test_expectation_overrides_default_implementation() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.foo_call().and_return(10));
+
+    assert_eq!(mock.bar(2), 12);
+}
+
+/// Tests that methods without default implementation are verified as usual.
+#[test]
+#[should_panic(expected = "unexpected call to `A#0.baz()`")]
+fn test_method_without_default_implementation() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    mock.baz();
+}
+
+#[mocked(skip(foo))]
+pub trait B {
+    fn foo(&self) -> u32 {
+        1
+    }
+    fn bar(&self, a: u32) -> u32 {
+        self.foo() + a
+    }
+}
+
+/// Tests that skipped methods always use default implementation.
+#[test]
+fn test_skipped_method_uses_default_implementation() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<B>();
+
+    scenario.expect(mock.bar_call(2).and_call(|a| a * 2));
+
+    assert_eq!(mock.foo(), 1);
+    assert_eq!(mock.bar(2), 4);
+}
@@ -0,0 +1,77 @@
+///! Test that mock may return default values for methods without expectations.
+use mockers::{Expectation, Scenario};
+use mockers_derive::mocked;
+
+pub struct NoDefault;
+
+#[mocked(default_returns)]
+pub trait A {
+    fn count(&self) -> u32;
+    fn name(&self, id: u32) -> String;
+    fn get(&self) -> NoDefault;
+}
+
+#[test]
+fn test_default_is_returned() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    assert_eq!(mock.count(), 0);
+    assert_eq!(mock.name(2), "");
+}
+
+#[test]
+fn test_expectation_overrides_default() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.count_call().and_return(5));
+    assert_eq!(mock.count(), 5);
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `A#0.name(3)`")]
+fn test_calls_are_verified_when_method_has_expectations() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.name_call(2).and_return("foo".to_owned()));
+    mock.name(3);
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `A#0.get()`")]
+fn test_type_without_default() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    mock.get();
+}
+
+#[test]
+fn test_sequence_expectation_overrides_default() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(
+        mock.name_call(2)
+            .and_return("foo".to_owned())
+            .then(mock.count_call().and_return(5)),
+    );
+    assert_eq!(mock.name(2), "foo");
+    assert_eq!(mock.count(), 5);
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `A#0.count()`")]
+fn test_sequence_expectation_is_verified_out_of_order() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(
+        mock.name_call(2)
+            .and_return("foo".to_owned())
+            .then(mock.count_call().and_return(5)),
+    );
+    mock.count();
+}
@@ -0,0 +1,45 @@
+///! Test that arguments with elided lifetimes nested in their types are supported.
+use std::borrow::Cow;
+
+use mockers::matchers::{check, ANY};
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Settings {
+    fn set(&self, value: Cow<'_, str>);
+    fn find(&self, key: Option<&str>) -> u32;
+    fn pair(&self, names: &[Cow<'_, str>]) -> usize;
+}
+
+#[test]
+fn test_cow_arg() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<dyn Settings>();
+
+    scenario.expect(mock.set_call(check(|v: &Cow<str>| v == "foo")).and_return(()));
+
+    let value = String::from("foo");
+    mock.set(Cow::Borrowed(&value));
+}
+
+#[test]
+fn test_nested_reference_arg() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<dyn Settings>();
+
+    scenario.expect(mock.find_call(ANY).and_return(2));
+
+    let key = String::from("key");
+    assert_eq!(mock.find(Some(&key)), 2);
+}
+
+#[test]
+fn test_cow_in_reference_arg() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<dyn Settings>();
+
+    scenario.expect(mock.pair_call(ANY).and_call(|names: &[Cow<str>]| names.len()));
+
+    assert_eq!(mock.pair(&[Cow::Borrowed("a"), Cow::Owned("b".to_owned())]), 2);
+}
@@ -1,6 +1,8 @@
-///! Test that extern functions may be mocked.
+///! Test that extern functions and statics may be mocked.
 use mockers_derive::mocked;
 
+use std::cell::Cell;
+
 use mockers::matchers::ANY;
 use mockers::Scenario;
 
@@ -14,6 +16,29 @@ extern "Rust" {
     fn bar();
 }
 
+#[mocked(CFoo)]
+extern "C" {
+    fn mockers_test_c_foo(arg: u32) -> u32;
+}
+
+#[mocked(CValue)]
+extern "C" {
+    static mockers_test_c_value: u32;
+}
+
+thread_local! {
+    static OPENED_FILES: Cell<u32> = Cell::new(0);
+}
+
+fn close_all_files() {
+    OPENED_FILES.with(|opened| opened.set(0));
+}
+
+#[mocked(Files, teardown = "close_all_files")]
+extern "Rust" {
+    fn mockers_test_open(path: u32) -> u32;
+}
+
 #[test]
 fn extern_function_can_be_mocked() {
     let scenario = Scenario::new();
@@ -32,6 +57,37 @@ fn only_one_mock_instance_of_same_type_is_allowed() {
     let _mock2 = scenario.create_mock::<Foo>();
 }
 
+#[test]
+fn mock_outliving_its_scenario_doesnt_block_new_mocks() {
+    {
+        let scenario = Scenario::new();
+        std::mem::forget(scenario.create_mock::<Foo>());
+    }
+
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<Foo>();
+
+    scenario.expect(mock.foo_call(2).and_return_default().times(1));
+
+    unsafe { foo(2) };
+}
+
+#[test]
+fn dropping_mock_outliving_its_scenario_keeps_new_mock_registered() {
+    let stale_mock = {
+        let scenario = Scenario::new();
+        scenario.create_mock::<Foo>()
+    };
+
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<Foo>();
+    drop(stale_mock);
+
+    scenario.expect(mock.foo_call(2).and_return_default().times(1));
+
+    unsafe { foo(2) };
+}
+
 #[test]
 fn mocks_of_different_types_can_be_used_simultaneously() {
     let scenario = Scenario::new();
@@ -44,3 +100,102 @@ fn mocks_of_different_types_can_be_used_simultaneously() {
     unsafe { foo(3) };
     unsafe { bar() };
 }
+
+fn create_first_dup_mock(scenario: &Scenario) -> impl Drop {
+    #[mocked(Dup)]
+    extern "Rust" {
+        fn mockers_test_first_dup();
+    }
+    scenario.create_mock::<Dup>()
+}
+
+fn create_second_dup_mock(scenario: &Scenario) -> impl Drop {
+    #[mocked(Dup)]
+    extern "Rust" {
+        fn mockers_test_second_dup();
+    }
+    scenario.create_mock::<Dup>()
+}
+
+#[test]
+fn equally_named_mocks_in_different_functions_are_distinguished() {
+    let scenario = Scenario::new();
+    let _first = create_first_dup_mock(&scenario);
+    let _second = create_second_dup_mock(&scenario);
+}
+
+#[test]
+fn extern_c_function_can_be_mocked() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CFoo>();
+
+    scenario.expect(mock.mockers_test_c_foo_call(3).and_return(4));
+
+    assert_eq!(unsafe { mockers_test_c_foo(3) }, 4);
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `CFoo#0.mockers_test_c_foo(3)`")]
+fn unexpected_call_to_extern_c_function_panics() {
+    let scenario = Scenario::new();
+    let _mock = scenario.create_mock::<CFoo>();
+
+    unsafe { mockers_test_c_foo(3) };
+}
+
+#[test]
+fn extern_static_can_be_mocked() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CValue>();
+
+    mock.set_mockers_test_c_value(5);
+
+    assert_eq!(unsafe { mockers_test_c_value() }, 5);
+}
+
+#[test]
+#[should_panic(expected = "value of extern static `mockers_test_c_value` isn't set")]
+fn extern_static_is_cleared_when_mock_is_dropped() {
+    {
+        let scenario = Scenario::new();
+        let mock = scenario.create_mock::<CValue>();
+        mock.set_mockers_test_c_value(5);
+    }
+
+    unsafe { mockers_test_c_value() };
+}
+
+#[test]
+fn extern_static_value_is_thread_local() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CValue>();
+    mock.set_mockers_test_c_value(5);
+
+    let other_thread = std::thread::spawn(|| {
+        let scenario = Scenario::new();
+        let mock = scenario.create_mock::<CValue>();
+        mock.set_mockers_test_c_value(7);
+        unsafe { mockers_test_c_value() }
+    });
+
+    assert_eq!(other_thread.join().unwrap(), 7);
+    assert_eq!(unsafe { mockers_test_c_value() }, 5);
+}
+
+#[test]
+fn teardown_is_called_when_mock_is_dropped() {
+    {
+        let scenario = Scenario::new();
+        let mock = scenario.create_mock::<Files>();
+
+        scenario.expect(mock.mockers_test_open_call(ANY).and_call(|path| {
+            OPENED_FILES.with(|opened| opened.set(opened.get() + 1));
+            path
+        }));
+
+        assert_eq!(unsafe { mockers_test_open(3) }, 3);
+        assert_eq!(OPENED_FILES.with(|opened| opened.get()), 1);
+    }
+
+    assert_eq!(OPENED_FILES.with(|opened| opened.get()), 0);
+}
@@ -0,0 +1,23 @@
+#![feature(c_variadic)]
+
+///! Test that variadic extern functions may be mocked.
+
+use mockers_derive::mocked;
+
+use mockers::Scenario;
+
+#[mocked(CLog)]
+extern "C" {
+    fn mockers_test_c_log(level: u32, ...) -> i32;
+}
+
+#[test]
+fn variadic_extern_function_can_be_mocked() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CLog>();
+
+    // Only fixed arguments are matched.
+    scenario.expect(mock.mockers_test_c_log_call(2).and_return(5));
+
+    assert_eq!(unsafe { mockers_test_c_log(2, 10u32, 20u32) }, 5);
+}
@@ -0,0 +1,61 @@
+///! Test fluent `expect_*().with(...).returning(...)` expectations.
+use mockers::matchers::{ANY, gt};
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Calc {
+    fn zero(&self) -> u32;
+    fn add(&self, a: u32, b: u32) -> u32;
+    fn chars(&self, s: &str) -> usize;
+    fn get<T: 'static>(&self) -> Option<T>;
+}
+
+#[test]
+fn test_fluent_no_args() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CalcMock>();
+    mock.expect_zero().with().returning(|| 0);
+    assert_eq!(mock.zero(), 0);
+}
+
+#[test]
+fn test_fluent_args() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CalcMock>();
+    mock.expect_add().with(2, gt(1)).returning(|a, b| a + b);
+    assert_eq!(mock.add(2, 3), 5);
+}
+
+#[test]
+#[should_panic(expected = "1 is not greater than 1")]
+fn test_fluent_args_mismatch() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CalcMock>();
+    mock.expect_add().with(2, gt(1)).returning(|a, b| a + b);
+    mock.add(2, 1);
+}
+
+#[test]
+fn test_fluent_ref_arg() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CalcMock>();
+    mock.expect_chars().with(ANY).returning(|s| s.len());
+    assert_eq!(mock.chars("abc"), 3);
+}
+
+#[test]
+fn test_fluent_generic_method() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CalcMock>();
+    mock.expect_get::<u8>().with().returning(|| Some(4));
+    assert_eq!(mock.get::<u8>(), Some(4));
+}
+
+#[test]
+#[should_panic(expected = "not satisfied")]
+fn test_fluent_expectation_is_verified() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CalcMock>();
+    mock.expect_zero().with().returning(|| 0);
+}
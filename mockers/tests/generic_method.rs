@@ -15,6 +15,13 @@ pub trait A {
     fn baz<'a, T>(&self, a: &'a T);
     fn qux<T: ToString>(&self, a: T);
     fn ret<T>(&self) -> T;
+    fn get<T>(&self, key: &str) -> T;
+    fn dup<T>(&self, a: T)
+    where
+        T: Clone;
+    fn parse<'a, T>(&self, s: Option<&'a str>) -> T
+    where
+        T: 'a;
 }
 
 #[test]
@@ -64,6 +71,17 @@ fn test_generic_method_with_parametrized_return_type() {
     assert_eq!(mock.ret::<u32>(), 2);
 }
 
+/// Test that type parameters of generic method may be given explicitly
+/// when creating expectation.
+#[test]
+fn test_generic_method_call_with_explicit_type_param() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.get_call::<u32>("key").and_return(2));
+    assert_eq!(mock.get::<u32>("key"), 2);
+}
+
 /// Test that usage of unregistered type as parameter of mocked generic method
 /// causes descriptive error
 #[test]
@@ -86,3 +104,34 @@ fn test_two_instantiations_of_generic_method_dont_match() {
     scenario.expect(mock.foo_call("foofoo").and_return(()));
     mock.foo::<u32>(2);
 }
+
+#[test]
+fn test_generic_method_with_where_clause() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.dup_call(2u32).and_return_default().times(1));
+    mock.dup(2u32);
+}
+
+/// Traits with generic methods have no `Mocked` impl, but mock type
+/// may be named with alias.
+#[test]
+fn test_mock_impl_alias() {
+    let scenario = Scenario::new();
+    let mock: AMockImpl = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.foo_call(2u32).and_return(()));
+    mock.foo(2u32);
+}
+
+#[test]
+fn test_generic_method_with_lifetime_and_type_params() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.parse_call::<u32>(Some("2")).and_return(2));
+
+    let input = String::from("2");
+    assert_eq!(mock.parse::<u32>(Some(&input)), 2);
+}
@@ -0,0 +1,44 @@
+///! Test that generic methods with `'static` type parameters may be mocked
+///! without registering types.
+use mockers::matchers::any;
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Registry {
+    fn get<T: 'static>(&self) -> T;
+    fn put<T>(&self, value: T)
+    where
+        T: 'static;
+}
+
+#[test]
+fn test_two_instantiations() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<RegistryMock>();
+
+    scenario.expect(mock.get_call::<u32>().and_return(2));
+    scenario.expect(mock.get_call::<String>().and_return("foo".to_owned()));
+
+    assert_eq!(mock.get::<String>(), "foo");
+    assert_eq!(mock.get::<u32>(), 2);
+}
+
+#[test]
+fn test_static_bound_in_where_clause() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<RegistryMock>();
+
+    scenario.expect(mock.put_call(any::<Vec<u8>>()).and_return(()));
+    mock.put(vec![1u8, 2]);
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `Registry#0.put(2)`")]
+fn test_instantiations_dont_match() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<RegistryMock>();
+
+    scenario.expect(mock.put_call(2u64).and_return(()));
+    mock.put(2u32);
+}
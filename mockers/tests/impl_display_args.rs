@@ -0,0 +1,43 @@
+///! Test that `impl Display` arguments are converted to strings.
+use std::fmt::Display;
+
+use mockers::matchers::ANY;
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Logger {
+    fn log(&self, msg: impl Display);
+    fn log_at(&self, level: u32, msg: impl std::fmt::Display + 'static) -> bool;
+}
+
+#[test]
+fn test_impl_display_arg() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<LoggerMock>();
+
+    scenario.expect(mock.log_call("3 items").and_return(()));
+    mock.log(format_args!("{} items", 3));
+}
+
+#[test]
+fn test_impl_display_arg_with_other_args() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<LoggerMock>();
+
+    scenario.expect(
+        mock.log_at_call(1, ANY)
+            .and_call(|_, msg: String| msg == "42"),
+    );
+    assert!(mock.log_at(1, 42));
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `Logger#0.log(\"other\")`")]
+fn test_impl_display_arg_mismatch() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<LoggerMock>();
+
+    scenario.expect(mock.log_call("message").and_return(()));
+    mock.log("other");
+}
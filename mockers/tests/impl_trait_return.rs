@@ -0,0 +1,52 @@
+///! Test that mockers can mock methods returning `impl Trait`.
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait A {
+    fn adder(&self) -> impl Fn(u32) -> u32;
+    fn items(&self) -> impl Iterator<Item = u32>;
+    fn iter(&self) -> impl Iterator<Item = u32> + '_;
+    fn names<'a>(&'a self) -> impl Iterator<Item = String> + 'a;
+}
+
+#[test]
+fn test_return_closure() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.adder_call().and_return(Box::new(|a: u32| a + 1)));
+
+    assert_eq!(mock.adder()(2), 3);
+}
+
+#[test]
+fn test_return_iterator() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.items_call().and_return(Box::new(vec![1, 2].into_iter())));
+
+    assert_eq!(mock.items().collect::<Vec<_>>(), vec![1, 2]);
+}
+
+#[test]
+fn test_return_iterator_borrowing_self() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.iter_call().and_return(Box::new(1..3)));
+
+    assert_eq!(mock.iter().collect::<Vec<_>>(), vec![1, 2]);
+}
+
+#[test]
+fn test_return_iterator_with_named_lifetime() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    let names = vec!["a".to_owned(), "b".to_owned()];
+    scenario.expect(mock.names_call().and_return(Box::new(names.into_iter())));
+
+    assert_eq!(mock.names().collect::<Vec<_>>(), vec!["a", "b"]);
+}
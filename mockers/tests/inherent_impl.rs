@@ -0,0 +1,56 @@
+///! Test that inherent methods of concrete types may be mocked.
+use mockers_derive::mocked;
+
+use mockers::matchers::ANY;
+use mockers::Scenario;
+
+pub struct Counter {
+    value: u32,
+}
+
+#[mocked]
+impl Counter {
+    pub fn get(&self) -> u32 {
+        self.value
+    }
+
+    pub fn add(&mut self, delta: u32) {
+        self.value += delta;
+    }
+}
+
+#[mocked(NamedCounterMock)]
+impl Counter {
+    pub fn name(&self) -> String {
+        "counter".to_owned()
+    }
+}
+
+#[test]
+fn test_inherent_methods() {
+    let scenario = Scenario::new();
+    let mut mock = scenario.create_mock::<CounterMock>();
+
+    scenario.expect(mock.add_call(ANY).and_return(()));
+    scenario.expect(mock.get_call().and_return(3));
+
+    mock.add(3);
+    assert_eq!(mock.get(), 3);
+}
+
+#[test]
+fn test_original_methods_are_kept() {
+    let mut counter = Counter { value: 1 };
+    counter.add(2);
+    assert_eq!(counter.get(), 3);
+}
+
+#[test]
+fn test_named_inherent_mock() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<NamedCounterMock>();
+
+    scenario.expect(mock.name_call().and_return("mock".to_owned()));
+
+    assert_eq!(mock.name(), "mock");
+}
@@ -0,0 +1,52 @@
+///! Test that mock may be boxed into trait object with `into_boxed`.
+use mockers::{Scenario, Sequence};
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Logger {
+    fn log(&self, msg: &str);
+}
+
+#[mocked]
+pub trait Source {
+    type Item;
+    fn next(&mut self) -> Option<Self::Item>;
+}
+
+struct Service {
+    logger: Box<dyn Logger>,
+}
+
+fn sum_all(mut source: Box<dyn Source<Item = u32>>) -> u32 {
+    let mut sum = 0;
+    while let Some(value) = source.next() {
+        sum += value;
+    }
+    sum
+}
+
+#[test]
+fn test_into_boxed() {
+    let scenario = Scenario::new();
+    let logger = scenario.create_mock_for::<dyn Logger>();
+
+    scenario.expect(logger.log_call("started").and_return(()));
+
+    let service = Service {
+        logger: logger.into_boxed(),
+    };
+    service.logger.log("started");
+}
+
+#[test]
+fn test_into_boxed_with_associated_types() {
+    let scenario = Scenario::new();
+    let source = scenario.create_mock_for::<dyn Source<Item = u32>>();
+
+    let mut seq = Sequence::new();
+    seq.expect(source.next_call().and_return(Some(2)));
+    seq.expect(source.next_call().and_return(None));
+    scenario.expect(seq);
+
+    assert_eq!(sum_all(source.into_boxed()), 2);
+}
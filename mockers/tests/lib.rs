@@ -1,8 +1,9 @@
+use std::cell::RefCell;
 use std::panic::AssertUnwindSafe;
 use std::rc::Rc;
 
-use mockers::matchers::{lt, ANY};
-use mockers::{Scenario, Sequence};
+use mockers::matchers::{any, lt, ANY};
+use mockers::{Expectation, Scenario, Sequence, UnexpectedCallAction};
 use mockers_derive::{mock, mocked};
 
 #[mocked]
@@ -11,6 +12,7 @@ pub trait A {
     fn bar(&self, arg: u32);
     fn baz(&self) -> u32;
     fn modify(&mut self);
+    /// Doc comments are copied to `ask_call`.
     fn ask(&self, arg: u32) -> u32;
     fn consume(self);
     fn consume_result(&self) -> String;
@@ -110,6 +112,38 @@ fn test_named_mock() {
     mock.foo();
 }
 
+#[test]
+fn test_mock_with_name() {
+    let scenario = Scenario::new();
+    let mock = AMock::with_name(&scenario, "amock");
+    assert_eq!(format!("{:?}", mock), "amock");
+}
+
+#[test]
+fn test_mock_id() {
+    let scenario = Scenario::new();
+    let first = scenario.create_mock::<AMock>();
+    let second = scenario.create_mock::<AMock>();
+    assert_ne!(first.mock_id(), second.mock_id());
+}
+
+/// Test that mock may be created for trait given both as `Trait`
+/// and as `dyn Trait`, these are the same trait object type.
+#[test]
+fn test_create_mock_for_dyn_trait() {
+    let scenario = Scenario::new();
+    let bare: AMock = scenario.create_mock_for::<A>();
+    let with_dyn: AMock = scenario.create_mock_for::<dyn A>();
+    let named: AMock = scenario.create_named_mock_for::<dyn A>("named".to_owned());
+
+    scenario.expect(bare.baz_call().and_return(1));
+    scenario.expect(with_dyn.baz_call().and_return(2));
+    scenario.expect(named.baz_call().and_return(3));
+    assert_eq!(bare.baz(), 1);
+    assert_eq!(with_dyn.baz(), 2);
+    assert_eq!(named.baz(), 3);
+}
+
 /// Test that when test is failed, then remaining scenario
 /// expectations are not checked and don't cause panic-during-drop
 /// which will lead to ugly failure with not very useful message.
@@ -185,6 +219,17 @@ fn test_never_not_satisfied() {
     mock.foo();
 }
 
+#[test]
+#[should_panic(expected = "A#0.bar should never be called")]
+fn test_never_with_typed_matcher_not_satisfied() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.bar_call(any()).never());
+
+    mock.bar(2);
+}
+
 #[test]
 fn test_consume_result() {
     let scenario = Scenario::new();
@@ -237,6 +282,66 @@ fn test_arguments_are_dropped_on_panic() {
     assert!(weak.upgrade().is_none());
 }
 
+#[test]
+fn test_unexpected_call_handler() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    let messages = Rc::new(RefCell::new(Vec::<String>::new()));
+    let messages_ref = messages.clone();
+    scenario.on_unexpected_call(move |msg| {
+        messages_ref.borrow_mut().push(msg.to_owned());
+        UnexpectedCallAction::Panic
+    });
+
+    let mock_ref = AssertUnwindSafe(&mock);
+    let result = std::panic::catch_unwind(|| mock_ref.bar(2));
+    assert!(result.is_err());
+
+    let messages = messages.borrow();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].contains("unexpected call to `A#0.bar(2)`"));
+}
+
+#[test]
+fn test_unexpected_call_handler_returns_default() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    let messages = Rc::new(RefCell::new(Vec::<String>::new()));
+    let messages_ref = messages.clone();
+    scenario.on_unexpected_call(move |msg| {
+        messages_ref.borrow_mut().push(msg.to_owned());
+        UnexpectedCallAction::ReturnDefault
+    });
+    scenario.expect(mock.ask_call(2).and_return(5));
+
+    assert_eq!(mock.ask(3), 0);
+    assert_eq!(mock.ask(2), 5);
+    assert_eq!(mock.baz(), 0);
+
+    let messages = messages.borrow();
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].contains("unexpected call to `A#0.ask(3)`"));
+    assert!(messages[1].contains("unexpected call to `A#0.baz()`"));
+}
+
+#[test]
+fn test_unexpected_call_handler_may_use_scenario() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    let handle = scenario.handle();
+    scenario.on_unexpected_call(move |_| {
+        let other = handle.create_mock_for::<A>();
+        handle.expect(other.foo_call().and_return(()));
+        other.foo();
+        UnexpectedCallAction::ReturnDefault
+    });
+
+    mock.foo();
+}
+
 #[test]
 #[should_panic(expected = "`A#0.foo() must be called exactly 2 times, called 1 times`")]
 fn test_checkpoint() {
@@ -311,6 +416,41 @@ fn test_sequence_invalid_order() {
     mock.foo();
 }
 
+/// Sequence may contain expectations of different mocks, then calls of all
+/// of them are ordered.
+#[test]
+fn test_sequence_of_several_mocks() {
+    let scenario = Scenario::new();
+    let first = scenario.create_mock_for::<A>();
+    let second = scenario.create_mock_for::<A>();
+
+    let mut seq = Sequence::new();
+    seq.expect(first.foo_call().and_return(()));
+    seq.expect(second.bar_call(4).and_return(()));
+    seq.expect(first.bar_call(5).and_return(()));
+    scenario.expect(seq);
+
+    first.foo();
+    second.bar(4);
+    first.bar(5);
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `A#1.bar(4)`")]
+fn test_sequence_of_several_mocks_invalid_order() {
+    let scenario = Scenario::new();
+    let first = scenario.create_mock_for::<A>();
+    let second = scenario.create_mock_for::<A>();
+
+    let mut seq = Sequence::new();
+    seq.expect(first.foo_call().and_return(()));
+    seq.expect(second.bar_call(4).and_return(()));
+    scenario.expect(seq);
+
+    second.bar(4);
+    first.foo();
+}
+
 #[test]
 fn test_sequence_times() {
     let scenario = Scenario::new();
@@ -341,6 +481,35 @@ fn test_sequence_times_invalid() {
     mock.bar(4);
 }
 
+#[test]
+fn test_sequence_chaining() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(
+        mock.foo_call()
+            .and_return(())
+            .then(mock.bar_call(4).and_return(()))
+            .then(mock.baz_call().and_return(2)),
+    );
+
+    mock.foo();
+    mock.bar(4);
+    assert_eq!(mock.baz(), 2);
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `A#0.bar(4)`")]
+fn test_sequence_chaining_invalid_order() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.foo_call().and_return(()).then(mock.bar_call(4).and_return(())));
+
+    mock.bar(4);
+    mock.foo();
+}
+
 #[test]
 fn test_return_default() {
     let scenario = Scenario::new();
@@ -350,3 +519,148 @@ fn test_return_default() {
 
     assert_eq!(mock.baz(), 0);
 }
+
+#[test]
+fn test_expectations_satisfied() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    let other = scenario.create_mock_for::<A>();
+    scenario.expect(mock.bar_call(2).and_return(()));
+    scenario.expect(other.foo_call().and_return(()));
+
+    assert!(!mock.expectations_satisfied());
+    mock.bar(2);
+    assert!(mock.expectations_satisfied());
+    assert!(!other.expectations_satisfied());
+
+    other.foo();
+}
+
+#[test]
+fn test_expectations_satisfied_with_sequence() {
+    let scenario = Scenario::new();
+    let first = scenario.create_mock_for::<A>();
+    let second = scenario.create_mock_for::<A>();
+    scenario.expect(first.foo_call().and_return(()).then(second.bar_call(4).and_return(())));
+
+    assert!(!first.expectations_satisfied());
+    assert!(!second.expectations_satisfied());
+    first.foo();
+    assert!(first.expectations_satisfied());
+    assert!(!second.expectations_satisfied());
+
+    second.bar(4);
+    assert!(second.expectations_satisfied());
+}
+
+#[test]
+fn test_reset() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    let other = scenario.create_mock_for::<A>();
+    scenario.expect(mock.bar_call(2).and_return(()));
+    scenario.expect(other.foo_call().and_return(()));
+
+    mock.reset();
+    assert!(mock.expectations_satisfied());
+    assert!(!other.expectations_satisfied());
+
+    scenario.expect(mock.bar_call(3).and_return(()));
+    mock.bar(3);
+    other.foo();
+}
+
+#[test]
+fn test_reset_with_sequence() {
+    let scenario = Scenario::new();
+    let first = scenario.create_mock_for::<A>();
+    let second = scenario.create_mock_for::<A>();
+    scenario.expect(
+        first.foo_call()
+            .and_return(())
+            .then(second.bar_call(4).and_return(()))
+            .then(first.bar_call(5).and_return(())),
+    );
+
+    first.reset();
+    assert!(first.expectations_satisfied());
+    assert!(!second.expectations_satisfied());
+    second.bar(4);
+}
+
+#[test]
+fn test_reset_sequence_member() {
+    let scenario = Scenario::new();
+    let first = scenario.create_mock_for::<A>();
+    let second = scenario.create_mock_for::<A>();
+    scenario.expect(
+        first.foo_call()
+            .and_return(())
+            .then(second.bar_call(4).and_return(()))
+            .then(first.bar_call(5).and_return(())),
+    );
+
+    second.reset();
+    assert!(second.expectations_satisfied());
+    first.foo();
+    first.bar(5);
+}
+
+/// Mock helper methods aren't generated when trait has own methods with
+/// the same names, so that trait methods aren't shadowed.
+#[mocked]
+pub trait Checker {
+    fn expectations_satisfied(&self) -> bool;
+    fn detached(&self) -> u32;
+}
+
+#[test]
+fn test_trait_methods_named_like_mock_helpers() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<Checker>();
+    scenario.expect(mock.expectations_satisfied_call().and_return(false));
+    scenario.expect(mock.detached_call().and_return(3));
+
+    assert!(!mock.expectations_satisfied());
+    assert_eq!(mock.detached(), 3);
+}
+
+#[test]
+fn test_return_sequence() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.ask_call(ANY).and_return_sequence(vec![1, 2, 3]));
+
+    assert_eq!(mock.ask(0), 1);
+    assert_eq!(mock.ask(0), 2);
+    assert_eq!(mock.ask(0), 3);
+}
+
+#[test]
+#[should_panic(
+    expected = "A#0.ask is called for the 3rd time, but expected to be called exactly 2 times"
+)]
+fn test_return_sequence_exhausted() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.ask_call(ANY).and_return_sequence(vec![1, 2]));
+
+    mock.ask(0);
+    mock.ask(0);
+    mock.ask(0);
+}
+
+fn check_type<T: A>(_: &T) {}
+
+#[test]
+fn test_detached_mock() {
+    let mock = AMock::detached();
+    check_type(&mock);
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `A#0.foo()`")]
+fn test_detached_mock_call() {
+    let mock = AMock::detached();
+    mock.foo();
+}
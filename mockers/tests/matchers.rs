@@ -1,4 +1,4 @@
-#[macro_use(arg, check)]
+#[macro_use(arg, check, all_of, any_of, elements)]
 extern crate mockers;
 
 use mockers::matchers::*;
@@ -11,8 +11,14 @@ pub trait A {
     fn noarg(&self);
     fn num(&self, arg: u32);
     fn cmplx(&self, maybe: Option<u32>);
+    fn float(&self, arg: f64);
+    fn bytes(&self, data: &[u8]);
+    fn handle(&self, h: Option<Handle>);
 }
 
+/// Type implementing neither `PartialEq` nor `Debug`.
+pub struct Handle(pub u32);
+
 #[test]
 fn test_any_match() {
     let scenario = Scenario::new();
@@ -21,6 +27,42 @@ fn test_any_match() {
     mock.bar(2);
 }
 
+#[test]
+fn test_typed_any_match() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    // Type parameter is inferred from `*_call` argument type.
+    scenario.expect(mock.bar_call(any()).and_return(()));
+    scenario.expect(mock.bytes_call(any()).and_return(()));
+    mock.bar(2);
+    mock.bytes(&[1, 2]);
+}
+
+#[test]
+fn test_value_match() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.num_call(2).and_return(()));
+    mock.num(2);
+}
+
+#[test]
+fn test_partial_eq_value_match() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.float_call(2.5).and_return(()));
+    mock.float(2.5);
+}
+
+#[test]
+#[should_panic(expected = "3.5 is not equal to 2.5")]
+fn test_partial_eq_value_mismatch() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.float_call(2.5).and_return(()));
+    mock.float(3.5);
+}
+
 #[test]
 fn test_eq_matcher_match() {
     let scenario = Scenario::new();
@@ -142,7 +184,7 @@ fn test_not_matcher_match() {
 }
 
 #[test]
-#[should_panic(expected = "2 matches (but shouldn\'t): lt(2)")]
+#[should_panic(expected = "2 matches (but shouldn\'t): ge(2)")]
 fn test_not_matcher_mismatch() {
     let scenario = Scenario::new();
     let mock = scenario.create_mock_for::<A>();
@@ -215,6 +257,79 @@ fn test_or_matcher_mismatch() {
     mock.num(4);
 }
 
+#[test]
+fn test_all_of_matcher_match() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.num_call(all_of!(gt(2), lt(5), ne(4))).and_return(()));
+    mock.num(3);
+}
+
+#[test]
+#[should_panic(expected = "4 is equal to 4 (failed ne(4))")]
+fn test_all_of_matcher_mismatch() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.num_call(all_of!(gt(2), lt(5), ne(4))).and_return(()));
+    mock.num(4);
+}
+
+#[test]
+fn test_any_of_matcher_match() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    let matchers: Vec<Box<dyn mockers::MatchArg<u32>>> = vec![Box::new(1), Box::new(gt(5))];
+    scenario.expect(mock.num_call(any_of(matchers)).and_return(()));
+    mock.num(6);
+}
+
+#[test]
+fn test_any_of_matcher_short_circuit() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.num_call(any_of!(lt(2), UnreachableMatcher)).and_return(()));
+    mock.num(1);
+}
+
+#[test]
+#[should_panic(
+    expected = "3 is not less than 2 (failed lt(2)) neither 3 is not greater than 5 (failed gt(5))"
+)]
+fn test_any_of_matcher_mismatch() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.num_call(any_of!(lt(2), gt(5))).and_return(()));
+    mock.num(3);
+}
+
+#[test]
+fn test_elements_matcher_match() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.bytes_call(elements!(1, ANY, gt(2))).and_return(()));
+    mock.bytes(&[1, 7, 3]);
+}
+
+#[test]
+#[should_panic(expected = "element #2: 2 is not greater than 2")]
+fn test_elements_matcher_element_mismatch() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(mock.bytes_call(elements!(1, ANY, gt(2))).and_return(()));
+    mock.bytes(&[1, 7, 2]);
+}
+
+#[test]
+#[should_panic(expected = "has 2 elements, but 3 are expected")]
+fn test_elements_matcher_length_mismatch() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    let matchers: Vec<Box<dyn mockers::MatchArg<u8>>> =
+        vec![Box::new(1), Box::new(ANY), Box::new(ANY)];
+    scenario.expect(mock.bytes_call(elements(matchers)).and_return(()));
+    mock.bytes(&[1, 7]);
+}
+
 #[test]
 fn test_arg_macro_match() {
     let scenario = Scenario::new();
@@ -278,6 +393,37 @@ fn test_check_macro_mismatch() {
     mock.cmplx(None);
 }
 
+fn is_big(n: &u32) -> Result<(), String> {
+    if *n > 10 {
+        Ok(())
+    } else {
+        Err(format!("{} is small", n))
+    }
+}
+
+#[test]
+fn test_matches_fn_match() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(
+        mock.num_call(matches_fn(is_big))
+            .and_return(()),
+    );
+    mock.num(11);
+}
+
+#[test]
+#[should_panic(expected = "3 is small")]
+fn test_matches_fn_mismatch() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+    scenario.expect(
+        mock.num_call(matches_fn(is_big))
+            .and_return(()),
+    );
+    mock.num(3);
+}
+
 #[test]
 fn test_range_match() {
     let scenario = Scenario::new();
@@ -331,6 +477,37 @@ fn test_none_mismatch() {
     mock.cmplx(Some(2));
 }
 
+#[test]
+fn test_none_match_without_partial_eq() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.handle_call(none()).and_return(()));
+
+    mock.handle(None);
+}
+
+#[test]
+#[should_panic(expected = "??? is not equal to None")]
+fn test_none_mismatch_without_debug() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.handle_call(none()).and_return(()));
+
+    mock.handle(Some(Handle(1)));
+}
+
+#[test]
+fn test_some_match_without_partial_eq() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.handle_call(some(check(|h: &Handle| h.0 == 1))).and_return(()));
+
+    mock.handle(Some(Handle(1)));
+}
+
 #[test]
 fn test_some_match() {
     let scenario = Scenario::new();
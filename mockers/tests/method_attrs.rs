@@ -0,0 +1,42 @@
+// `#[must_use]` on trait implementation method would trigger `unused_attributes`,
+// and default method body copied into mock compiles only if `allow` is kept.
+#![deny(unused_attributes, unused_variables)]
+
+///! Test that attributes given in `keep_attrs` are kept on trait
+///! implementation methods, and `#[must_use]` isn't copied.
+
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait A {
+    #[must_use]
+    fn compute(&self, a: u32) -> u32;
+}
+
+#[mocked(delegate_defaults, keep_attrs(allow, inline))]
+pub trait B {
+    #[allow(unused_variables)]
+    #[inline]
+    fn check(&self) -> bool {
+        let unchecked = false;
+        true
+    }
+}
+
+#[test]
+fn test_must_use_method() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.compute_call(2).and_return(4));
+    assert_eq!(mock.compute(2), 4);
+}
+
+#[test]
+fn test_keep_attrs() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<B>();
+
+    assert!(mock.check());
+}
@@ -0,0 +1,43 @@
+///! Test that free functions may be mocked with `mock_mod!`.
+use mockers::matchers::ANY;
+use mockers::Scenario;
+
+mod fs {
+    use mockers_derive::mock_mod;
+
+    mock_mod! {
+        FsMock,
+        pub fn read(path: &str) -> String;
+        pub fn remove(path: &str);
+    }
+}
+
+use self::fs::{read, remove, FsMock};
+
+fn cleanup(path: &str) -> bool {
+    if read(path).is_empty() {
+        remove(path);
+        true
+    } else {
+        false
+    }
+}
+
+#[test]
+fn test_free_functions_can_be_mocked() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<FsMock>();
+
+    scenario.expect(mock.read_call("a.txt").and_return(String::new()));
+    scenario.expect(mock.remove_call(ANY).and_return(()));
+
+    assert!(cleanup("a.txt"));
+}
+
+#[test]
+#[should_panic(expected = "Mock FsMock for module already exists")]
+fn test_only_one_mock_instance_is_allowed() {
+    let scenario = Scenario::new();
+    let _mock1 = scenario.create_mock::<FsMock>();
+    let _mock2 = scenario.create_mock::<FsMock>();
+}
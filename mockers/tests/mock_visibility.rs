@@ -0,0 +1,55 @@
+///! Test that visibility of mock struct may be set explicitly.
+use mockers::Scenario;
+use mockers_derive::{mock, mocked};
+
+mod inner {
+    use mockers_derive::mocked;
+
+    #[mocked(vis = "pub(crate)")]
+    pub trait A {
+        fn foo(&self, a: u32);
+    }
+}
+
+use self::inner::{A, AMock};
+
+pub trait B {
+    fn bar(&self) -> u32;
+}
+
+mock! {
+    pub(crate) BMock,
+    self,
+    trait B {
+        fn bar(&self) -> u32;
+    }
+}
+
+#[mocked(CMockPrivate, vis = "pub(crate)")]
+pub trait C {
+    fn baz(&self);
+}
+
+#[test]
+fn test_attr_visibility() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+    scenario.expect(mock.foo_call(2).and_return(()));
+    mock.foo(2);
+}
+
+#[test]
+fn test_macro_visibility() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<BMock>();
+    scenario.expect(mock.bar_call().and_return(3));
+    assert_eq!(mock.bar(), 3);
+}
+
+#[test]
+fn test_named_mock_visibility() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CMockPrivate>();
+    scenario.expect(mock.baz_call().and_return(()));
+    mock.baz();
+}
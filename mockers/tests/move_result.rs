@@ -0,0 +1,47 @@
+///! Test that values which can't be cloned may be returned from one-shot actions.
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+/// Resource which can't be cloned, like file handle.
+#[derive(Debug, PartialEq)]
+pub struct Resource {
+    pub fd: u32,
+}
+
+#[mocked]
+pub trait Opener {
+    fn open(&self, path: &str) -> Resource;
+}
+
+#[test]
+fn test_return_non_clone_value() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<OpenerMock>();
+
+    scenario.expect(mock.open_call("a").and_return(Resource { fd: 3 }));
+
+    assert_eq!(mock.open("a"), Resource { fd: 3 });
+}
+
+#[test]
+fn test_call_moves_captured_value() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<OpenerMock>();
+
+    let resource = Resource { fd: 4 };
+    scenario.expect(mock.open_call("a").and_call(move |_| resource));
+
+    assert_eq!(mock.open("a"), Resource { fd: 4 });
+}
+
+#[test]
+#[should_panic(expected = "Opener#0.open was already called earlier")]
+fn test_non_clone_value_is_returned_once() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<OpenerMock>();
+
+    scenario.expect(mock.open_call("a").and_return(Resource { fd: 3 }));
+
+    mock.open("a");
+    mock.open("a");
+}
@@ -30,6 +30,17 @@ mod derive_inherited_trait {
         mock.foo(3);
         mock.bar(4);
     }
+
+    /// Method names in messages are qualified with trait name, because
+    /// several traits may have methods with the same name.
+    #[test]
+    #[should_panic(expected = "B::bar(4)`")]
+    fn test_qualified_method_name() {
+        let scenario = Scenario::new();
+        let mock = scenario.create_mock::<BMock>();
+
+        mock.bar(4);
+    }
 }
 
 /// Test mocking of inherited trait in different modules using `mocked`.
@@ -70,6 +81,123 @@ mod derive_inherited_trait_different_modules {
     }
 }
 
+/// Test resolving inherited traits by module glob in `refs`.
+mod derive_inherited_trait_glob_refs {
+    use super::*;
+
+    mod io {
+        use mockers_derive::mocked;
+
+        #[mocked(module = "::derive_inherited_trait_glob_refs::io")]
+        pub trait A {
+            fn foo(&self, a: u32);
+        }
+
+        #[mocked(module = "::derive_inherited_trait_glob_refs::io")]
+        pub trait C {
+            fn baz(&self, c: u32);
+        }
+    }
+
+    mod b {
+        use mockers_derive::mocked;
+
+        #[mocked(refs = "super::io::* => ::derive_inherited_trait_glob_refs::io::*")]
+        pub trait B: super::io::A + super::io::C {
+            fn bar(&self, b: u32);
+        }
+    }
+
+    #[test]
+    fn test() {
+        use self::b::B;
+        use self::io::{A, C};
+
+        let scenario = Scenario::new();
+        let mock = scenario.create_mock::<b::BMock>();
+
+        scenario.expect(mock.foo_call(ANY).and_return_default().times(1));
+        scenario.expect(mock.baz_call(ANY).and_return_default().times(1));
+        scenario.expect(mock.bar_call(ANY).and_return_default().times(1));
+
+        mock.foo(3);
+        mock.baz(5);
+        mock.bar(4);
+    }
+}
+
+/// Test resolving inherited trait which is re-exported under another name.
+mod derive_inherited_reexported_trait {
+    use super::*;
+
+    mod base {
+        use mockers_derive::mocked;
+
+        #[mocked(module = "::derive_inherited_reexported_trait::base")]
+        pub trait Base {
+            fn foo(&self, a: u32);
+        }
+    }
+
+    mod api {
+        pub use super::base::Base as Parent;
+    }
+
+    mod derived {
+        use mockers_derive::mocked;
+
+        #[mocked(refs = "super::api::Parent => ::derive_inherited_reexported_trait::base::Base")]
+        pub trait Derived: super::api::Parent {
+            fn bar(&self, b: u32);
+        }
+    }
+
+    #[test]
+    fn test() {
+        use self::api::Parent;
+        use self::derived::Derived;
+
+        let scenario = Scenario::new();
+        let mock = scenario.create_mock::<derived::DerivedMock>();
+
+        scenario.expect(mock.foo_call(ANY).and_return_default().times(1));
+        scenario.expect(mock.bar_call(ANY).and_return_default().times(1));
+
+        mock.foo(3);
+        mock.bar(4);
+    }
+}
+
+/// Test mocking of trait inherited from trait defined in another crate.
+mod derive_inherited_foreign_trait {
+    use super::*;
+    use mockers_derive::{mocked, register_trait};
+    use std::fmt;
+
+    register_trait!(::std::fmt, trait Write {
+        fn write_str(&mut self, s: &str) -> ::std::fmt::Result;
+    });
+
+    #[mocked(refs = "fmt::Write => ::std::fmt::Write")]
+    pub trait Sink: fmt::Write {
+        fn flush(&self);
+    }
+
+    #[test]
+    fn test() {
+        use std::fmt::Write;
+
+        let scenario = Scenario::new();
+        let mut mock = scenario.create_mock::<SinkMock>();
+
+        scenario.expect(mock.write_str_call("foo").and_return(Ok(())));
+        scenario.expect(mock.flush_call().and_return_default().times(1));
+
+        mock.write_str("foo").unwrap();
+        mock.flush();
+    }
+}
+
 // Test mocking of inherited trait.
 mod inherited_trait {
     use super::*;
@@ -154,6 +282,62 @@ mod multi_trait {
     }
 }
 
+// Test adding traits to mock declared by previous `mock!` invocation.
+mod extended_mock {
+    use super::*;
+    use mockers_derive::mock;
+
+    pub trait A {
+        fn foo(&self, a: u32);
+    }
+
+    pub trait B {
+        fn bar(&self, b: u32) -> u32;
+    }
+
+    mock! {
+        ABMock,
+
+        self,
+        trait A {
+            fn foo(&self, a: u32);
+        }
+    }
+
+    mock! {
+        extend ABMock,
+
+        self,
+        trait B {
+            fn bar(&self, b: u32) -> u32;
+        }
+    }
+
+    fn accept_ab<T: A + B>(t: T) -> u32 {
+        t.foo(1);
+        t.bar(2)
+    }
+
+    #[test]
+    fn test() {
+        let scenario = Scenario::new();
+        let mock = scenario.create_mock::<ABMock>();
+
+        scenario.expect(mock.foo_call(1).and_return(()));
+        scenario.expect(mock.bar_call(2).and_return(3));
+
+        assert_eq!(accept_ab(mock), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected call to `A#0.B::bar(4)`")]
+    fn test_unexpected_call() {
+        let scenario = Scenario::new();
+        let mock = scenario.create_mock::<ABMock>();
+        mock.bar(4);
+    }
+}
+
 // Test that it is possible to specify parent trait when using `mock!`.
 /// It is currently not used, but may be used in the future, so syntax
 /// should be allowed.
@@ -182,3 +366,48 @@ mod inherited_trait_with_specified_parent {
         }
     }
 }
+
+// Test mocking several traits having associated types with the same name.
+mod same_associated_type_names {
+    use super::*;
+    use mockers_derive::mock;
+
+    pub trait A {
+        type Error;
+        fn foo(&self) -> Result<(), Self::Error>;
+    }
+
+    pub trait B {
+        type Error;
+        fn bar(&self) -> Result<(), Self::Error>;
+    }
+
+    mock! {
+        ABMock,
+
+        self,
+        trait A {
+            type Error;
+            fn foo(&self) -> Result<(), Self::Error>;
+        },
+
+        self,
+        trait B {
+            type Error;
+            fn bar(&self) -> Result<(), Self::Error>;
+        }
+    }
+
+    #[test]
+    fn test() {
+        let scenario = Scenario::new();
+        // Type parameters are named `AError` and `BError`.
+        let mock = scenario.create_mock::<ABMock<u32, String>>();
+
+        scenario.expect(mock.foo_call().and_return(Err(1)));
+        scenario.expect(mock.bar_call().and_return(Err("b".to_owned())));
+
+        assert_eq!(mock.foo(), Err(1));
+        assert_eq!(mock.bar(), Err("b".to_owned()));
+    }
+}
@@ -0,0 +1,39 @@
+///! Test that mutable reference arguments may be inspected and written by reactions.
+use mockers_derive::mocked;
+
+use mockers::matchers::{check, ANY};
+use mockers::Scenario;
+
+#[mocked]
+pub trait Reader {
+    fn fill(&self, buf: &mut [u8]) -> usize;
+}
+
+fn read_header<R: Reader>(reader: &R) -> Vec<u8> {
+    let mut buf = [0u8; 4];
+    let len = reader.fill(&mut buf);
+    buf[..len].to_vec()
+}
+
+#[test]
+fn test_write_to_mut_ref_arg() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<Reader>();
+
+    scenario.expect(mock.fill_call(ANY).and_call(|buf: &mut [u8]| {
+        buf[..2].copy_from_slice(b"hi");
+        2
+    }));
+
+    assert_eq!(read_header(&mock), b"hi".to_vec());
+}
+
+#[test]
+fn test_match_mut_ref_arg() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<Reader>();
+
+    scenario.expect(mock.fill_call(check(|buf: &&mut [u8]| buf.len() == 4)).and_return(0));
+
+    assert_eq!(read_header(&mock), Vec::<u8>::new());
+}
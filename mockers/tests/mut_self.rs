@@ -0,0 +1,33 @@
+///! Test that methods with `&mut self` receiver are mocked.
+use mockers::matchers::ANY;
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Clock {
+    fn advance(&mut self);
+    fn advance_by(&mut self, ticks: u32) -> u32;
+    fn now(&self) -> u32;
+}
+
+/// Accepts trait object, so mock must implement `&mut self` methods
+/// with exactly the same receivers.
+fn run(clock: &mut dyn Clock, steps: u32) -> u32 {
+    for _ in 0..steps {
+        clock.advance();
+    }
+    clock.advance_by(10);
+    clock.now()
+}
+
+#[test]
+fn test_mut_self_methods() {
+    let scenario = Scenario::new();
+    let mut clock = scenario.create_mock_for::<dyn Clock>();
+
+    scenario.expect(clock.advance_call().and_return_default().times(2));
+    scenario.expect(clock.advance_by_call(ANY).and_call(|ticks| ticks));
+    scenario.expect(clock.now_call().and_return(12));
+
+    assert_eq!(run(&mut clock, 2), 12);
+}
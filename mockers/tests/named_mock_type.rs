@@ -1,4 +1,4 @@
-///! Test that mock may be named using attribute parameter.
+///! Test that mock may be named using attribute parameters.
 use mockers_derive::mocked;
 
 use mockers::matchers::ANY;
@@ -16,3 +16,41 @@ fn test_extern() {
     scenario.expect(mock.foo_call(ANY).and_return(()));
     mock.foo(3);
 }
+
+#[test]
+fn test_mock_impl_alias() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMockImpl>();
+    scenario.expect(mock.foo_call(ANY).and_return(()));
+    mock.foo(3);
+}
+
+#[mocked(mock_name_pattern = "Mock{}")]
+pub trait B {
+    fn bar(&self, a: u32);
+}
+
+#[test]
+fn test_mock_name_pattern() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<MockB>();
+    scenario.expect(mock.bar_call(ANY).and_return(()));
+    mock.bar(3);
+}
+
+pub struct C;
+
+#[mocked(mock_name_pattern = "{}Stub")]
+impl C {
+    pub fn baz(&self) -> u32 {
+        1
+    }
+}
+
+#[test]
+fn test_mock_name_pattern_for_inherent_impl() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<CStub>();
+    scenario.expect(mock.baz_call().and_return(2));
+    assert_eq!(mock.baz(), 2);
+}
@@ -0,0 +1,44 @@
+///! Test that `*_call` methods may return `impl CallMatchReactions`.
+use mockers::matchers::ANY;
+use mockers::{CallMatchReactions, Scenario};
+use mockers_derive::mocked;
+
+#[mocked(opaque_call_match)]
+pub trait A {
+    fn foo(&self);
+    fn add(&self, a: u32, b: u32) -> u32;
+    fn name(&self, key: &str) -> String;
+}
+
+#[test]
+fn test_and_return() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.foo_call().and_return(()));
+    scenario.expect(mock.name_call("a").and_return("b".to_owned()));
+
+    mock.foo();
+    assert_eq!(mock.name("a"), "b");
+}
+
+#[test]
+fn test_and_call() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.add_call(ANY, 2).and_call(|a, b| a + b));
+
+    assert_eq!(mock.add(1, 2), 3);
+}
+
+#[test]
+#[should_panic(expected = "A#0.foo should never be called")]
+fn test_never() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.foo_call().never());
+
+    mock.foo();
+}
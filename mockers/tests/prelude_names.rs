@@ -0,0 +1,57 @@
+///! Test that traits named like prelude traits may be mocked.
+use mockers::Scenario;
+
+mod default {
+    use super::*;
+    use mockers_derive::mocked;
+
+    #[mocked]
+    pub trait Default {
+        fn default(&self) -> u32;
+    }
+
+    #[test]
+    fn test_default() {
+        let scenario = Scenario::new();
+        let mock = scenario.create_mock_for::<Default>();
+        scenario.expect(mock.default_call().and_return(2));
+        assert_eq!(mock.default(), 2);
+    }
+}
+
+mod clone {
+    use super::*;
+    use mockers_derive::mocked;
+
+    #[mocked]
+    pub trait Clone {
+        fn clone(self: Box<Self>, times: u32) -> Vec<u32>;
+    }
+
+    #[test]
+    fn test_clone() {
+        let scenario = Scenario::new();
+        let mock = scenario.create_mock::<CloneMock>();
+        scenario.expect(mock.clone_call(2).and_return(vec![1, 1]));
+        assert_eq!(Box::new(mock).clone(2), vec![1, 1]);
+    }
+}
+
+mod iterator {
+    use super::*;
+    use mockers_derive::mocked;
+
+    #[mocked]
+    pub trait Iterator {
+        type Item;
+        fn next(&mut self) -> Option<Self::Item>;
+    }
+
+    #[test]
+    fn test_iterator() {
+        let scenario = Scenario::new();
+        let mut mock = scenario.create_mock::<IteratorMock<u32>>();
+        scenario.expect(mock.next_call().and_return(Some(1)));
+        assert_eq!(mock.next(), Some(1));
+    }
+}
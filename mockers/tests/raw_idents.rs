@@ -0,0 +1,31 @@
+///! Test that methods named with raw identifiers may be mocked.
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait A {
+    fn r#match(&self, r#type: u32) -> bool;
+    fn r#foo(&self);
+}
+
+#[test]
+fn test_raw_ident_method() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.match_call(2).and_return(true));
+    scenario.expect(mock.foo_call().and_return(()));
+
+    assert!(mock.r#match(2));
+    mock.foo();
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `A#0.match(3)`")]
+fn test_raw_ident_in_messages() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.match_call(2).and_return(true));
+    mock.r#match(3);
+}
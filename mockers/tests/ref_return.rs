@@ -0,0 +1,30 @@
+///! Test that mockers can mock methods returning references borrowed from `self`.
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Named {
+    fn name(&self) -> &str;
+    fn alias<'a>(&'a self, idx: u32) -> Option<&'a str>;
+}
+
+#[test]
+fn test_return_ref() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<NamedMock>();
+
+    scenario.expect(mock.name_call().and_return("foo"));
+
+    assert_eq!(mock.name(), "foo");
+}
+
+#[test]
+fn test_return_ref_with_named_lifetime() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<NamedMock>();
+
+    let alias: &'static str = Box::leak(format!("bar{}", 1).into_boxed_str());
+    scenario.expect(mock.alias_call(1).and_return(Some(alias)));
+
+    assert_eq!(mock.alias(1), Some("bar1"));
+}
@@ -7,6 +7,7 @@ use mockers::Scenario;
 #[mocked]
 pub trait A {
     fn foo(&self, a: &u32);
+    fn name(&self, n: &str);
 }
 
 #[test]
@@ -28,3 +29,15 @@ fn test_refs_comparison() {
 
     mock.foo(&2);
 }
+
+#[test]
+fn test_refs_comparison_with_owned_value() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.foo_call(2u32).and_return_default().times(1));
+    scenario.expect(mock.name_call("foo".to_owned()).and_return_default().times(1));
+
+    mock.foo(&2);
+    mock.name("foo");
+}
@@ -0,0 +1,23 @@
+///! Test that methods consuming `self` can be mocked.
+use mockers_derive::mocked;
+
+use mockers::Scenario;
+
+#[mocked]
+pub trait A {
+    fn consume(self) -> String;
+}
+
+fn consume_a<T: A>(a: T) -> String {
+    a.consume()
+}
+
+#[test]
+fn test_method_taking_self_by_value() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<A>();
+
+    scenario.expect(mock.consume_call().and_return("consumed".to_owned()));
+
+    assert_eq!(consume_a(mock), "consumed");
+}
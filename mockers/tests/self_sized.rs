@@ -0,0 +1,29 @@
+///! Test that methods with `where Self: Sized` bound may be mocked.
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait A {
+    fn foo(&self, a: u32) -> u32 where Self: Sized;
+    fn bar(&self, a: u32);
+}
+
+#[test]
+fn test_sized_method() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.foo_call(2).and_return(3));
+    assert_eq!(mock.foo(2), 3);
+}
+
+/// Methods bound by `Self: Sized` don't prevent using trait objects.
+#[test]
+fn test_trait_object() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.bar_call(2).and_return_default().times(1));
+    let obj: &dyn A = &mock;
+    obj.bar(2);
+}
@@ -0,0 +1,95 @@
+///! Test that `Self` nested in other types is resolved properly.
+use mockers_derive::mocked;
+
+use std::collections::HashMap;
+
+use mockers::matchers::{check, ANY};
+use mockers::Scenario;
+
+#[mocked]
+pub trait Resource {
+    fn create(name: &str) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn children(&self) -> Vec<Self>
+    where
+        Self: Sized;
+    fn try_clone(&self) -> Result<Self, String>
+    where
+        Self: Sized;
+    fn table() -> HashMap<String, Self>
+    where
+        Self: Sized;
+}
+
+/// Trait with `Self` in argument position, it isn't object safe,
+/// so mock is created by mock type.
+#[mocked]
+pub trait Shape {
+    fn overlaps(&self, other: &Self) -> bool;
+    fn merge(&mut self, other: Self);
+}
+
+#[test]
+fn test_static_method_returning_result_of_self() {
+    let scenario = Scenario::new();
+    let static_mock = scenario.create_mock::<ResourceMockStatic>();
+
+    scenario.expect(static_mock.create_call("bad").and_return(Err("failed".to_owned())));
+
+    assert!(<ResourceMock as Resource>::create("bad").is_err());
+}
+
+#[test]
+fn test_static_method_returning_self_inside_type_arguments() {
+    let scenario = Scenario::new();
+    let static_mock = scenario.create_mock::<ResourceMockStatic>();
+    let child = scenario.create_mock::<ResourceMock>();
+
+    let mut table = HashMap::new();
+    table.insert("child".to_owned(), child);
+    scenario.expect(static_mock.table_call().and_return(table));
+
+    let table = <ResourceMock as Resource>::table();
+    assert!(table.contains_key("child"));
+}
+
+#[test]
+fn test_methods_returning_self_inside_containers() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<ResourceMock>();
+    let child = scenario.create_mock::<ResourceMock>();
+
+    scenario.expect(mock.try_clone_call().and_return(Err("can't clone".to_owned())));
+    scenario.expect(mock.children_call().and_return(vec![child]));
+
+    assert_eq!(mock.try_clone().err(), Some("can't clone".to_owned()));
+    assert_eq!(mock.children().len(), 1);
+}
+
+#[test]
+fn test_method_with_self_argument() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<ShapeMock>();
+    let other = scenario.create_mock::<ShapeMock>();
+
+    let other_id = other.mock_id();
+    scenario.expect(
+        mock.overlaps_call(check(move |s: &&ShapeMock| s.mock_id() == other_id))
+            .and_return(true),
+    );
+
+    assert!(mock.overlaps(&other));
+}
+
+#[test]
+fn test_method_with_self_argument_by_value() {
+    let scenario = Scenario::new();
+    let mut mock = scenario.create_mock::<ShapeMock>();
+    let other = scenario.create_mock::<ShapeMock>();
+
+    scenario.expect(mock.merge_call(ANY).and_return(()));
+
+    mock.merge(other);
+}
+
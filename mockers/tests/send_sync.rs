@@ -0,0 +1,21 @@
+///! Test that mocks may be sent to other threads when `unsafe_send_sync` option is used.
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked(unsafe_send_sync)]
+pub trait A {
+    fn foo(&self, a: u32);
+}
+
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+#[test]
+fn test_send_to_thread() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    scenario.expect(mock.foo_call(2).and_return_default().times(1));
+
+    assert_send_sync(&mock);
+    std::thread::spawn(move || mock.foo(2)).join().unwrap();
+}
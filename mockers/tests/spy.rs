@@ -0,0 +1,77 @@
+///! Test that spy mock delegates calls without expectations to real implementation.
+use std::collections::HashMap;
+
+use mockers::{Expectation, Scenario};
+use mockers_derive::mocked;
+
+#[mocked(spy)]
+pub trait Store {
+    fn get(&self, key: &str) -> Option<String>;
+    fn put(&mut self, key: &str, value: String);
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool;
+}
+
+#[derive(Default)]
+struct MemoryStore(HashMap<String, String>);
+
+impl Store for MemoryStore {
+    fn get(&self, key: &str) -> Option<String> {
+        self.0.get(key).cloned()
+    }
+
+    fn put(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_owned(), value);
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[test]
+fn test_spy_delegates_calls() {
+    let scenario = Scenario::new();
+    let mut mock = StoreMock::spy(&scenario, Box::new(MemoryStore::default()));
+
+    mock.put("a", "1".to_owned());
+    assert_eq!(mock.get("a"), Some("1".to_owned()));
+    assert_eq!(mock.len(), 1);
+}
+
+#[test]
+fn test_spy_expectations_take_precedence() {
+    let scenario = Scenario::new();
+    let mut mock = StoreMock::spy(&scenario, Box::new(MemoryStore::default()));
+    scenario.expect(mock.len_call().and_return(10));
+
+    mock.put("a", "1".to_owned());
+    assert_eq!(mock.len(), 10);
+    assert_eq!(mock.get("a"), Some("1".to_owned()));
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `Store#0.len()`")]
+fn test_spy_without_delegate() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<StoreMock>();
+    mock.len();
+}
+
+#[test]
+#[should_panic(expected = "unexpected call to `Store#0.len()`")]
+fn test_spy_doesnt_delegate_calls_expected_later_in_sequence() {
+    let scenario = Scenario::new();
+    let mock = StoreMock::spy(&scenario, Box::new(MemoryStore::default()));
+    scenario.expect(
+        mock.is_empty_call()
+            .and_return(true)
+            .then(mock.len_call().and_return(10)),
+    );
+
+    mock.len();
+}
@@ -83,3 +83,92 @@ fn mock_trait_with_ctor() {
 
     create_and_use::<WithCtorMock>();
 }
+
+#[mocked(static_mock_name = "BazStaticMock")]
+trait Baz {
+    fn baz() -> u32;
+}
+
+#[test]
+fn static_mock_name_may_be_set_explicitly() {
+    let scenario = Scenario::new();
+    let static_mock = scenario.create_mock::<BazStaticMock>();
+
+    scenario.expect(static_mock.baz_call().and_return(2));
+
+    assert_eq!(BazMock::baz(), 2);
+}
+
+#[mocked]
+trait WithItemCtor {
+    type Item;
+    fn new() -> Self;
+    fn make(n: u32) -> Self::Item;
+    fn get(&self) -> Self::Item;
+}
+
+fn create_and_get<T: WithItemCtor>() -> (T::Item, T::Item) {
+    let t = T::new();
+    (T::make(1), t.get())
+}
+
+#[test]
+fn mock_trait_with_ctor_and_associated_type() {
+    let scenario = Scenario::new();
+    let static_mock = scenario.create_mock::<WithItemCtorMockStatic<u32>>();
+
+    scenario.expect(static_mock.new_call().and_call({
+        let scenario = scenario.handle();
+        move || {
+            let mock = scenario.create_mock::<WithItemCtorMock<u32>>();
+            scenario.expect(mock.get_call().and_return(3));
+            mock
+        }
+    }));
+    scenario.expect(static_mock.make_call(1).and_return(2));
+
+    assert_eq!(create_and_get::<WithItemCtorMock<u32>>(), (2, 3));
+}
+
+#[mocked(no_static_mock)]
+trait Single {
+    fn new() -> Self;
+    fn foo(&self, arg: u32);
+    fn bar(arg: u32);
+}
+
+fn create_and_use_single<S: Single>() {
+    let s = S::new();
+    s.foo(3);
+    S::bar(2);
+}
+
+/// Tests that expectations for static methods may be set using main mock
+/// when `no_static_mock` option is given.
+#[test]
+fn static_methods_on_main_mock() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<SingleMock>();
+
+    scenario.expect(mock.new_call().and_call({
+        let scenario = scenario.handle();
+        move || {
+            // Second mock is allowed, static calls still go to the first one.
+            let instance = scenario.create_mock::<SingleMock>();
+            scenario.expect(instance.foo_call(3).and_return(()));
+            instance
+        }
+    }));
+    scenario.expect(mock.bar_call(2).and_return(()));
+
+    create_and_use_single::<SingleMock>();
+}
+
+#[test]
+#[should_panic(expected = "Mock SingleMock for static methods already exists")]
+fn main_mocks_from_different_scenarios_conflict() {
+    let scenario1 = Scenario::new();
+    let scenario2 = Scenario::new();
+    let _mock1 = scenario1.create_mock::<SingleMock>();
+    let _mock2 = scenario2.create_mock::<SingleMock>();
+}
@@ -0,0 +1,24 @@
+///! Test that mocks with `test_only` option may be created in tests.
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked(test_only)]
+pub trait A {
+    fn foo(&self) -> u32;
+    fn create() -> u32
+    where
+        Self: Sized;
+}
+
+#[test]
+fn test_mock_creation_in_tests() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+    let static_mock = scenario.create_mock::<AMockStatic>();
+
+    scenario.expect(mock.foo_call().and_return(2));
+    scenario.expect(static_mock.create_call().and_return(3));
+
+    assert_eq!(mock.foo(), 2);
+    assert_eq!(<AMock as A>::create(), 3);
+}
@@ -0,0 +1,32 @@
+///! Test that unexpected call panic is reported at location of mocked method call.
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait A {
+    fn foo(&self, a: u32);
+}
+
+#[test]
+fn test_unexpected_call_location() {
+    let location = Arc::new(Mutex::new(None));
+    panic::set_hook(Box::new({
+        let location = location.clone();
+        move |info| {
+            *location.lock().unwrap() = info.location().map(|l| (l.file().to_owned(), l.line()));
+        }
+    }));
+
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<AMock>();
+
+    let line = line!() + 1;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| mock.foo(2)));
+    let _ = panic::take_hook();
+
+    assert!(result.is_err());
+    assert_eq!(*location.lock().unwrap(), Some((file!().to_owned(), line)));
+}
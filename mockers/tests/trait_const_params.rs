@@ -0,0 +1,44 @@
+///! Test that traits with const parameters can be mocked.
+use mockers::Scenario;
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait Buffer<const N: usize> {
+    fn capacity(&self) -> usize;
+    fn fill(&self, data: [u8; N]);
+}
+
+#[test]
+fn test_trait_with_const_parameter() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<BufferMock<16>>();
+
+    scenario.expect(mock.capacity_call().and_return(16));
+    scenario.expect(mock.fill_call([0; 16]).and_return(()));
+
+    assert_eq!(mock.capacity(), 16);
+    mock.fill([0; 16]);
+}
+
+#[test]
+fn test_create_mock_for_trait_with_const_parameter() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<Buffer<16>>();
+
+    scenario.expect(mock.capacity_call().and_return(16));
+    assert_eq!(mock.capacity(), 16);
+}
+
+#[mocked]
+pub trait Storage<T, const N: usize> {
+    fn get(&self, index: usize) -> T;
+}
+
+#[test]
+fn test_trait_with_type_and_const_parameters() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<StorageMock<u32, 4>>();
+
+    scenario.expect(mock.get_call(1).and_return(5));
+    assert_eq!(mock.get(1), 5);
+}
@@ -0,0 +1,26 @@
+///! Test that traits with lifetime parameters can be mocked.
+use mockers_derive::mocked;
+
+use mockers::Scenario;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Token<'a>(&'a str);
+
+#[mocked]
+pub trait Parser<'a> {
+    fn parse(&self, input: &'a str) -> Token<'a>;
+}
+
+fn parse_all<'a, P: Parser<'a>>(parser: &P, input: &'a str) -> Token<'a> {
+    parser.parse(input)
+}
+
+#[test]
+fn test_trait_with_lifetime_parameter() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<ParserMock<'static>>();
+
+    scenario.expect(mock.parse_call("foo").and_return(Token("foo")));
+
+    assert_eq!(parse_all(&mock, "foo"), Token("foo"));
+}
@@ -0,0 +1,52 @@
+///! Test that traits with type parameters can be mocked.
+use mockers_derive::mocked;
+
+use std::hash::Hash;
+
+use mockers::Scenario;
+
+#[mocked]
+pub trait Repository<T> {
+    fn get(&self, id: u32) -> T;
+    fn put(&self, id: u32, item: T);
+}
+
+#[test]
+fn test_trait_with_type_parameter() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock::<RepositoryMock<u32>>();
+
+    scenario.expect(mock.put_call(1, 10).and_return(()));
+    scenario.expect(mock.get_call(1).and_return(10));
+
+    mock.put(1, 10);
+    assert_eq!(mock.get(1), 10);
+}
+
+#[test]
+fn test_create_mock_for_trait_with_type_parameter() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<Repository<u32>>();
+
+    scenario.expect(mock.get_call(2).and_return(20));
+
+    assert_eq!(mock.get(2), 20);
+}
+
+#[mocked]
+pub trait Store<K>
+where
+    K: Hash + Eq,
+{
+    fn get(&self, key: K) -> Option<u32>;
+}
+
+#[test]
+fn test_trait_with_where_clause() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<Store<&'static str>>();
+
+    scenario.expect(mock.get_call("a").and_return(Some(1)));
+
+    assert_eq!(mock.get("a"), Some(1));
+}
@@ -0,0 +1,95 @@
+///! Test that unsatisfied expectations may be inspected as structured data.
+use mockers::{Expectation, Scenario, Sequence, UnmetExpectation};
+use mockers_derive::mocked;
+
+#[mocked]
+pub trait A {
+    fn foo(&self);
+    fn bar(&self, a: u32);
+}
+
+#[test]
+fn test_unmet_expectations() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<dyn A>();
+
+    scenario.expect(mock.foo_call().and_return(()));
+    scenario.expect(mock.bar_call(2).and_return_default().times(3));
+    scenario.expect(mock.bar_call(5).and_return_default().times(0..2));
+    mock.bar(2);
+
+    assert_eq!(
+        scenario.unmet_expectations(),
+        vec![
+            UnmetExpectation {
+                mock_name: "A#0".to_owned(),
+                method_name: "foo",
+                call: "foo()".to_owned(),
+                expected_calls: "called exactly one time".to_owned(),
+                calls_count: 0,
+            },
+            UnmetExpectation {
+                mock_name: "A#0".to_owned(),
+                method_name: "bar",
+                call: "bar(2)".to_owned(),
+                expected_calls: "called exactly 3 times".to_owned(),
+                calls_count: 1,
+            },
+        ]
+    );
+
+    mock.reset();
+    assert!(scenario.unmet_expectations().is_empty());
+}
+
+#[test]
+fn test_unmet_expectations_of_mock() {
+    let scenario = Scenario::new();
+    let mock_a = scenario.create_mock_for::<dyn A>();
+    let mock_b = scenario.create_mock_for::<dyn A>();
+
+    let mut seq = Sequence::new();
+    seq.expect(mock_a.foo_call().and_return(()));
+    seq.expect(mock_b.bar_call(4).and_return(()));
+    scenario.expect(seq);
+    scenario.expect(mock_b.foo_call().and_return(()));
+
+    let unmet: Vec<_> = mock_b.unmet_expectations().into_iter().map(|e| e.call).collect();
+    assert_eq!(unmet, vec!["bar(4)".to_owned(), "foo()".to_owned()]);
+    let unmet: Vec<_> = mock_a.unmet_expectations().into_iter().map(|e| e.call).collect();
+    assert_eq!(unmet, vec!["foo()".to_owned()]);
+
+    // Each call queued in sequence is reported for its own mock.
+    mock_a.foo();
+    let unmet: Vec<_> = mock_b.unmet_expectations().into_iter().map(|e| e.call).collect();
+    assert_eq!(unmet, vec!["bar(4)".to_owned(), "foo()".to_owned()]);
+    assert!(mock_a.unmet_expectations().is_empty());
+
+    mock_b.bar(4);
+    mock_b.foo();
+}
+
+#[test]
+fn test_unmet_expectations_of_sequence() {
+    let scenario = Scenario::new();
+    let mock_a = scenario.create_mock_for::<dyn A>();
+    let mock_b = scenario.create_mock_for::<dyn A>();
+
+    scenario.expect(mock_a.foo_call().and_return(()).then(mock_b.bar_call(4).and_return(())));
+
+    let unmet: Vec<_> = scenario
+        .unmet_expectations()
+        .into_iter()
+        .map(|e| (e.mock_name, e.call))
+        .collect();
+    assert_eq!(
+        unmet,
+        vec![
+            ("A#0".to_owned(), "foo()".to_owned()),
+            ("A#1".to_owned(), "bar(4)".to_owned()),
+        ]
+    );
+
+    mock_a.foo();
+    mock_b.bar(4);
+}
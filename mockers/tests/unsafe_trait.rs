@@ -0,0 +1,23 @@
+///! Test that unsafe traits can be mocked.
+use mockers_derive::mocked;
+
+use mockers::Scenario;
+
+#[mocked]
+pub unsafe trait RawHandle {
+    fn as_ptr(&self) -> *const u8;
+}
+
+fn handle_is_null<H: RawHandle>(handle: &H) -> bool {
+    handle.as_ptr().is_null()
+}
+
+#[test]
+fn test_unsafe_trait() {
+    let scenario = Scenario::new();
+    let mock = scenario.create_mock_for::<RawHandle>();
+
+    scenario.expect(mock.as_ptr_call().and_return(std::ptr::null()));
+
+    assert!(handle_is_null(&mock));
+}
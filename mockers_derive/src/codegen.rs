@@ -1,40 +1,68 @@
 use itertools::Itertools;
 use proc_macro2::{Span, TokenStream};
 use quote::ToTokens;
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
 use std::result::Result;
 use std::sync::Mutex;
 use syn::{
-    parse_quote, punctuated::Punctuated, AngleBracketedGenericArguments, ArgCaptured, BareFnArg,
-    Binding, Expr, FnArg, FnDecl, ForeignItem, ForeignItemFn, GenericArgument, GenericParam,
-    Generics, Ident, ImplItemType, Item, ItemTrait, Lifetime, ParenthesizedGenericArguments, Pat,
-    PatIdent, Path, PathArguments, PathSegment, QSelf, ReturnType, Token, TraitBound,
-    TraitBoundModifier, TraitItem, TraitItemMethod, TraitItemType, Type, TypeArray, TypeBareFn,
-    TypeGroup, TypeParamBound, TypeParen, TypePath, TypePtr, TypeReference, TypeSlice, TypeTuple,
-    ItemStruct, ItemImpl, TypeParam,
+    parse_quote, punctuated::Punctuated, Abi, AngleBracketedGenericArguments, ArgCaptured, ArgSelfRef,
+    Attribute,
+    BareFnArg, Binding, Block, Expr, FnArg, FnDecl, ForeignItem, ForeignItemFn, ForeignItemStatic,
+    ConstParam, Error, GenericArgument, GenericParam, Generics, Ident, ImplItem, ImplItemType, Item, ItemTrait,
+    Lifetime, LitStr, ParenthesizedGenericArguments, Pat, PatIdent, Path, PathArguments, PathSegment, QSelf,
+    ReturnType, Token, TraitBound, TraitBoundModifier, TraitItem, TraitItemMethod, TraitItemType,
+    Type, TypeArray, TypeBareFn, TypeGroup, TypeImplTrait, TypeParamBound, TypeParen, TypePath,
+    TypePtr, TypeReference, TypeSlice, TypeTraitObject, TypeTuple, ItemStruct, ItemImpl, TypeParam,
+    Visibility, PredicateType, WhereClause, WherePredicate, MethodSig,
 };
 
-use crate::options::{parse_macro_args, MockAttrOptions, TraitDesc};
+use crate::options::{parse_macro_args, parse_mock_mod_args, MockAttrOptions, TraitDesc};
 
 use std::iter::FromIterator;
 
+static mut NEXT_REGISTERED_TYPE_ID: usize = 0;
+
+/// Maximum number of method arguments, it is limited by number of
+/// `CallMatchN` types defined in `mockers` crate.
+const MAX_ARGS_COUNT: usize = 4;
+
+lazy_static! {
+    //static ref KNOWN_TRAITS: Mutex<HashMap<Path, Item>> = Mutex::new(HashMap::new());
+    static ref KNOWN_TRAITS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
+
 /// Each mock struct generated with `#[derive(Mock)]` or `mock!` gets
 /// unique type ID. It is added to both call matchers produced by
 /// `*_call` methods and to `Call` structure created by mocked method.
 /// It is same to use call matcher for inspecting call object only when
 /// both mock type ID and method name match.
-static mut NEXT_MOCK_TYPE_ID: usize = 0;
-
-static mut NEXT_REGISTERED_TYPE_ID: usize = 0;
+///
+/// Returns constant expression computing ID from name of mock type given by
+/// `mock_type_name_expr`.
+fn mock_type_id_expr(key: &str) -> TokenStream {
+    let mock_type_name = mock_type_name_expr(key);
+    quote! {{
+        const MOCK_TYPE_ID: usize = ::mockers::mock_type_id(#mock_type_name);
+        MOCK_TYPE_ID
+    }}
+}
 
-lazy_static! {
-    //static ref KNOWN_TRAITS: Mutex<HashMap<Path, Item>> = Mutex::new(HashMap::new());
-    static ref KNOWN_TRAITS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+/// Returns expression giving unique name of mock type: given key qualified
+/// with path of module where mock is defined, which isn't known to macro,
+/// and with position of macro invocation, so that equally named mocks defined
+/// in different functions of the same module are distinguished.
+fn mock_type_name_expr(key: &str) -> TokenStream {
+    quote! {
+        ::std::concat!(
+            ::std::module_path!(), "::", #key, "@", ::std::line!(), ":", ::std::column!()
+        )
+    }
 }
 
-pub fn mocked_impl(input: TokenStream, opts: &MockAttrOptions) -> Result<TokenStream, String> {
+pub fn mocked_impl(input: TokenStream, opts: &MockAttrOptions) -> Result<TokenStream, Error> {
     let mut result = input.clone();
-    let source_item: Item = syn::parse2(input).map_err(|e| e.to_string())?;
+    let source_item: Item = syn::parse2(input)?;
     let (tokens, include_source) = generate_mock(&source_item, opts)?;
 
     if cfg!(feature = "debug") {
@@ -48,11 +76,10 @@ pub fn mocked_impl(input: TokenStream, opts: &MockAttrOptions) -> Result<TokenSt
     Ok(result)
 }
 
-pub fn register_types_impl(input: TokenStream) -> Result<TokenStream, String> {
+pub fn register_types_impl(input: TokenStream) -> Result<TokenStream, Error> {
     use syn::parse::Parser;
     let types = Punctuated::<Type, Token![,]>::parse_separated_nonempty
-        .parse2(input)
-        .map_err(|e| e.to_string())?;
+        .parse2(input)?;
 
     // Generate struct local to crate, so that trait implementation can be written.
     let item_struct: ItemStruct = parse_quote!{
@@ -91,27 +118,100 @@ pub fn register_types_impl(input: TokenStream) -> Result<TokenStream, String> {
     })
 }
 
-fn generate_mock(item: &Item, opts: &MockAttrOptions) -> Result<(TokenStream, bool), String> {
+pub fn register_trait_impl(input: TokenStream) -> Result<TokenStream, Error> {
+    let desc: TraitDesc = syn::parse2(input)?;
+    if desc.mod_path.leading_colon.is_none() {
+        return Err(Error::new_spanned(&desc.mod_path, "module path must be global"));
+    }
+    register_known_trait(&desc.mod_path, &desc.trait_item);
+
+    // Nothing is generated, trait definition is just remembered, so it
+    // can be referenced by traits mocked later.
+    Ok(TokenStream::new())
+}
+
+/// Remembers full trait definition, so it can be recalled when it is
+/// referenced by another trait.
+fn register_known_trait(module_path: &Path, item_trait: &ItemTrait) {
+    let mut full_path = module_path.clone();
+    full_path
+        .segments
+        .push(PathSegment::from(item_trait.ident.clone()));
+    KNOWN_TRAITS.lock().unwrap().insert(
+        full_path.into_token_stream().to_string(),
+        item_trait.into_token_stream().to_string(),
+    );
+}
+
+fn generate_mock(item: &Item, opts: &MockAttrOptions) -> Result<(TokenStream, bool), Error> {
     match item {
-        Item::Trait(trait_item) => Ok((generate_trait_mock(trait_item, opts)?, true)),
         Item::ForeignMod(foreign_mod) => {
             let mock_name = opts.mock_name.as_ref().ok_or_else(|| {
-                "mock type name must be set explicitly for extern block".to_string()
+                Error::new_spanned(
+                    &foreign_mod.abi,
+                    "mock type name must be set explicitly for extern block",
+                )
             })?;
-            Ok((generate_extern_mock(foreign_mod, mock_name)?, false))
+            Ok((generate_extern_mock(foreign_mod, mock_name, opts.teardown.as_ref())?, false))
         }
-        _ => Err("Attribute may be used on traits and extern blocks only".to_string()),
+        _ if opts.teardown.is_some() => Err(Error::new_spanned(
+            &opts.teardown,
+            "teardown may be used for extern blocks only",
+        )),
+        Item::Trait(trait_item) => {
+            let tokens = generate_trait_mock(trait_item, opts)?;
+            let cfg_attrs: Vec<Attribute> =
+                trait_item.attrs.iter().filter(|a| is_cfg_attr(a)).cloned().collect();
+            Ok((add_cfg_attrs(tokens, &cfg_attrs)?, true))
+        }
+        Item::Impl(item_impl) => Ok((generate_impl_mock(item_impl, opts)?, true)),
+        _ => Err(Error::new_spanned(
+            item,
+            "Attribute may be used on traits, impl blocks and extern blocks only",
+        )),
+    }
+}
+
+/// Returns mock struct name: either explicitly given one or built from name
+/// of mocked type using `mock_name_pattern`, `{}Mock` by default.
+fn default_mock_ident(opts: &MockAttrOptions, mocked_name: &str) -> Ident {
+    opts.mock_name.clone().unwrap_or_else(|| {
+        let pattern = opts.mock_name_pattern.as_ref().map_or("{}Mock", String::as_str);
+        Ident::new(&pattern.replace("{}", mocked_name), Span::call_site())
+    })
+}
+
+/// Resolves relative path of referenced trait to global one using `refs`
+/// parameter. Exact entries are checked first, then module globs are tried
+/// in order until known trait is found.
+fn resolve_trait_ref(path: &Path, opts: &MockAttrOptions) -> Option<Path> {
+    if let Some(full_path) = opts.refs.get(path) {
+        return Some(full_path.clone());
     }
+
+    let known_traits = KNOWN_TRAITS.lock().unwrap();
+    opts.ref_modules
+        .iter()
+        .filter(|&(source, _)| {
+            path.leading_colon.is_none()
+                && path.segments.len() > source.segments.len()
+                && path.segments.iter().zip(source.segments.iter()).all(|(a, b)| a == b)
+        })
+        .map(|(source, target)| {
+            let mut full_path = target.clone();
+            full_path
+                .segments
+                .extend(path.segments.iter().skip(source.segments.len()).cloned());
+            full_path
+        })
+        .find(|full_path| known_traits.contains_key(&full_path.into_token_stream().to_string()))
 }
 
 fn generate_trait_mock(
     item_trait: &ItemTrait,
     opts: &MockAttrOptions,
-) -> Result<TokenStream, String> {
-    let mock_ident = opts
-        .mock_name
-        .clone()
-        .unwrap_or_else(|| Ident::new(&format!("{}Mock", item_trait.ident), Span::call_site()));
+) -> Result<TokenStream, Error> {
+    let mock_ident = default_mock_ident(opts, &unraw(&item_trait.ident));
 
     // Find definitions for referenced traits.
     let referenced_items =
@@ -121,20 +221,20 @@ fn generate_trait_mock(
             .map(|b| {
                 let path = match *b {
                     TypeParamBound::Lifetime(..) => {
-                        return Err("lifetime parameters not supported yet".to_string());
+                        return Err(Error::new_spanned(b, "lifetime parameters not supported yet"));
                     }
                     TypeParamBound::Trait(TraitBound { ref path, .. }) => path,
                 };
-                let full_path = if path.leading_colon.is_some() {
-                    path
-                } else {
-                    match opts.refs.get(path) {
-                        Some(p) => p,
-                        None => {
-                            return Err(
-                                "parent trait path must be given using 'refs' param".to_string()
-                            );
-                        }
+                // Global path is used as is unless `refs` maps it to another
+                // one, e.g. when trait is re-exported.
+                let full_path = match resolve_trait_ref(path, opts) {
+                    Some(p) => p,
+                    None if path.leading_colon.is_some() => path.clone(),
+                    None => {
+                        return Err(Error::new_spanned(
+                            path,
+                            "parent trait path must be given using 'refs' param",
+                        ));
                     }
                 };
                 if let Some(referenced_trait) = KNOWN_TRAITS
@@ -148,28 +248,27 @@ fn generate_trait_mock(
                             path.segments.iter().take(path.segments.len() - 1).cloned(),
                         ),
                     };
-                    let referenced_trait: ItemTrait = syn::parse_str(&referenced_trait).unwrap();
+                    let mut referenced_trait: ItemTrait = syn::parse_str(referenced_trait).unwrap();
+                    // Trait may be re-exported under another name, e.g.
+                    // `pub use base::Base as Parent`, so it is named as referenced.
+                    referenced_trait.ident = path.segments.last().unwrap().value().ident.clone();
                     Ok(TraitDesc {
                         mod_path: mod_path,
-                        trait_item: referenced_trait.clone(),
+                        trait_item: referenced_trait,
                     })
                 } else {
-                    Err(format!("Can't resolve trait reference: {:?}", path))
+                    Err(Error::new_spanned(
+                        path,
+                        format!("Can't resolve trait reference: {:?}", path),
+                    ))
                 }
             })
-            .collect::<Result<Vec<TraitDesc>, String>>()?;
+            .collect::<Result<Vec<TraitDesc>, Error>>()?;
 
     // Remember full trait definition, so we can recall it when it is references by
     // another trait.
     if let Some(ref module_path) = opts.module_path {
-        let mut full_path = module_path.clone();
-        full_path
-            .segments
-            .push(PathSegment::from(item_trait.ident.clone()));
-        KNOWN_TRAITS.lock().unwrap().insert(
-            full_path.into_token_stream().to_string(),
-            item_trait.into_token_stream().to_string(),
-        );
+        register_known_trait(module_path, item_trait);
     }
 
     let trait_desc = TraitDesc {
@@ -181,40 +280,48 @@ fn generate_trait_mock(
     };
     let mut all_traits = referenced_items;
     all_traits.push(trait_desc);
-    generate_mock_for_traits(mock_ident, &all_traits, true)
+    generate_mock_for_traits(mock_ident, &all_traits, true, false, opts)
 }
 
 /// Generate mock struct and all implementations for given `trait_items`.
 /// `mock_ident` is identifier for mock struct.
 /// If `local` is `true`, `Mocked` instance generated for mock, which
 /// allows to use `scenario.create_mock_for::<Trait>`.
+/// When `extend` is true, only implementations of given traits are generated
+/// for mock struct which is already defined by previous `mock!` invocation.
 fn generate_mock_for_traits(
     mock_ident: Ident,
     trait_items: &[TraitDesc],
     local: bool,
-) -> Result<TokenStream, String> {
+    extend: bool,
+    opts: &MockAttrOptions,
+) -> Result<TokenStream, Error> {
     let mock_ident_ref = &mock_ident;
     // Validate items, reject unsupported ones.
     let mut trait_paths = HashSet::<String>::new();
-    let traits: Vec<(Path, &Vec<TraitItem>)> = trait_items
+    let traits: Vec<(Path, &ItemTrait)> = trait_items
         .iter()
         .map(|desc| {
             match desc.trait_item {
                 ItemTrait {
-                    unsafety,
                     ref generics,
                     ref supertraits,
-                    ref items,
                     ..
                 } => {
-                    if unsafety.is_some() {
-                        return Err("Unsafe traits are not supported yet".to_string());
-                    }
-
-                    if !generics.params.is_empty() || !generics.where_clause.is_none() {
-                        return Err("Parametrized traits are not supported yet".to_string());
+                    for param in &generics.params {
+                        match param {
+                            GenericParam::Type(..) => {}
+                            GenericParam::Lifetime(ref def) => {
+                                if !def.bounds.is_empty() {
+                                    return Err(Error::new_spanned(
+                                        def,
+                                        "Bounds on trait lifetime parameters are not supported yet",
+                                    ));
+                                }
+                            }
+                            GenericParam::Const(..) => {}
+                        }
                     }
-
                     for bound in supertraits {
                         match *bound {
                             TypeParamBound::Trait(TraitBound {
@@ -233,21 +340,25 @@ fn generate_mock_for_traits(
                                         if !trait_paths
                                             .contains(&path.clone().into_token_stream().to_string())
                                         {
-                                            return Err("All base trait definitions must be \
-                                                        provided"
-                                                .to_string());
+                                            return Err(Error::new_spanned(
+                                                path,
+                                                "All base trait definitions must be provided",
+                                            ));
                                         }
                                     }
                                     _ => {
-                                        return Err("Type bound modifiers are not supported yet"
-                                            .to_string());
+                                        return Err(Error::new_spanned(
+                                            bound,
+                                            "Type bound modifiers are not supported yet",
+                                        ));
                                     }
                                 }
                             }
                             TypeParamBound::Lifetime(..) => {
-                                return Err(
-                                    "Lifetime parameter bounds are not supported yet".to_string()
-                                );
+                                return Err(Error::new_spanned(
+                                    bound,
+                                    "Lifetime parameter bounds are not supported yet",
+                                ));
                             }
                         }
                     }
@@ -262,136 +373,410 @@ fn generate_mock_for_traits(
                         "{}",
                         trait_path.clone().into_token_stream().to_string()
                     ));
-                    Ok((trait_path, items))
+                    Ok((trait_path, &desc.trait_item))
                 }
             }
         })
-        .collect::<Result<Vec<(Path, &Vec<TraitItem>)>, String>>()?;
+        .collect::<Result<Vec<(Path, &ItemTrait)>, Error>>()?;
+
+    // Skipped method names are checked, so that misspelled name isn't ignored silently.
+    for ident in &opts.skip {
+        let exists = traits
+            .iter()
+            .flat_map(|&(_, t)| t.items.iter())
+            .any(|member| match member {
+                TraitItem::Method(TraitItemMethod { ref sig, .. }) => sig.ident == *ident,
+                _ => false,
+            });
+        if !exists {
+            return Err(Error::new_spanned(
+                ident,
+                format!("there is no method {} to skip", ident),
+            ));
+        }
+    }
+
+    // Gather lifetime, type and const parameters from all traits, they become
+    // parameters of mock struct.
+    let mut lifetimes = Vec::<Lifetime>::new();
+    let mut trait_type_params = Vec::<TypeParam>::new();
+    let mut const_params = Vec::<ConstParam>::new();
+    for &(_, item_trait) in &traits {
+        for def in item_trait.generics.lifetimes() {
+            if lifetimes.contains(&def.lifetime) {
+                return Err(Error::new_spanned(
+                    &def.lifetime,
+                    format!("lifetime {} is used by several traits", def.lifetime),
+                ));
+            }
+            lifetimes.push(def.lifetime.clone());
+        }
+        for param in item_trait.generics.type_params() {
+            if trait_type_params.iter().any(|p| p.ident == param.ident) {
+                return Err(Error::new_spanned(
+                    &param.ident,
+                    format!("type parameter {} is used by several traits", param.ident),
+                ));
+            }
+            trait_type_params.push(TypeParam {
+                attrs: Vec::new(),
+                eq_token: None,
+                default: None,
+                ..param.clone()
+            });
+        }
+        for param in item_trait.generics.const_params() {
+            if const_params.iter().any(|p| p.ident == param.ident) {
+                return Err(Error::new_spanned(
+                    &param.ident,
+                    format!("const parameter {} is used by several traits", param.ident),
+                ));
+            }
+            const_params.push(ConstParam {
+                attrs: Vec::new(),
+                eq_token: None,
+                default: None,
+                ..param.clone()
+            });
+        }
+    }
+    let const_args: Vec<Ident> = const_params.iter().map(|p| p.ident.clone()).collect();
+
+    // Gather `where` clause predicates from all traits, they are added to
+    // mock struct and all its impls. Predicates bounding `Self` are skipped,
+    // they are checked when trait is implemented for mock anyway.
+    let mut where_predicates = Punctuated::<WherePredicate, Token![,]>::new();
+    for &(_, item_trait) in &traits {
+        if let Some(ref where_clause) = item_trait.generics.where_clause {
+            for predicate in &where_clause.predicates {
+                if !is_self_predicate(predicate) {
+                    where_predicates.push(predicate.clone());
+                }
+            }
+        }
+    }
+    let where_clause: Option<WhereClause> = if where_predicates.is_empty() {
+        None
+    } else {
+        Some(parse_quote! { where #where_predicates })
+    };
 
     // Gather associated types from all traits, because they are used in mock
-    // struct definition.
+    // struct definition. Bounds of associated types become bounds of
+    // corresponding type parameters.
+    // Several traits may have associated types with the same name, type
+    // parameters for them are prefixed with trait name, e.g. `AError`
+    // and `BError` for `A::Error` and `B::Error`.
+    let mut assoc_type_counts = HashMap::<String, usize>::new();
+    for &(_, item_trait) in &traits {
+        for member in item_trait.items.iter() {
+            if let TraitItem::Type(TraitItemType { ref ident, .. }) = member {
+                *assoc_type_counts.entry(ident.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+    let has_assoc_type_collisions = assoc_type_counts.values().any(|&count| count > 1);
     let mut assoc_types = Vec::new();
-    for &(_, ref members) in &traits {
-        for member in members.iter() {
+    let mut assoc_type_params = Vec::<TypeParam>::new();
+    // Associated type names paired with corresponding type parameters, for each trait.
+    let mut trait_assoc_types = Vec::<Vec<(Ident, Ident)>>::new();
+    for &(_, item_trait) in &traits {
+        let mut pairs = Vec::new();
+        for member in item_trait.items.iter() {
             if let TraitItem::Type(TraitItemType {
                 ref ident,
+                ref generics,
                 ref bounds,
                 ..
             }) = member
             {
-                if !bounds.is_empty() {
-                    return Err("associated type bounds are not supported yet".to_string());
+                // Associated type becomes type parameter of mock struct, but
+                // generic associated type can't be expressed this way.
+                if !generics.params.is_empty() || generics.where_clause.is_some() {
+                    return Err(Error::new_spanned(
+                        member,
+                        format!(
+                            "generic associated type {} is not supported yet, \
+                             only associated types without own parameters may be mocked",
+                            ident
+                        ),
+                    ));
                 }
-                assoc_types.push(ident.clone());
+                let param_ident = if assoc_type_counts[&ident.to_string()] > 1 {
+                    Ident::new(&format!("{}{}", item_trait.ident, ident), Span::call_site())
+                } else {
+                    ident.clone()
+                };
+                let mut param: TypeParam = parse_quote! { #param_ident };
+                param.bounds = bounds.clone();
+                assoc_types.push(param_ident.clone());
+                assoc_type_params.push(param);
+                pairs.push((ident.clone(), param_ident));
             }
         }
+        trait_assoc_types.push(pairs);
+    }
+
+    // All type parameters of mock struct: trait type parameters followed
+    // by associated types.
+    let type_params: Vec<Ident> = trait_type_params
+        .iter()
+        .map(|p| p.ident.clone())
+        .chain(assoc_types.iter().cloned())
+        .collect();
+
+    // Existing mock struct can't get new parameters.
+    if extend && !(lifetimes.is_empty() && type_params.is_empty() && const_params.is_empty()) {
+        return Err(Error::new_spanned(
+            &traits[0].1.ident,
+            "traits added to existing mock can't have generic parameters or associated types",
+        ));
     }
 
-    let struct_item = generate_mock_struct(&mock_ident, &assoc_types);
+    // Visibility of mock structs, `pub` unless set explicitly.
+    let vis: Visibility = opts.vis.clone().unwrap_or_else(|| parse_quote! { pub });
+
+    // With `spy` option mock holds optional real implementation of trait,
+    // e.g. `Box<dyn A<'a, T, Item=Item>>`.
+    let delegate_type = if opts.spy {
+        if traits.len() > 1 {
+            return Err(Error::new_spanned(
+                &traits[1].1.ident,
+                "spy option can't be used for mock of several traits",
+            ));
+        }
+        let (ref trait_path, item_trait) = traits[0];
+        let trait_args = generic_args(&item_trait.generics);
+        let assoc_names = trait_assoc_types[0].iter().map(|(assoc, _)| assoc);
+        let assoc_params = trait_assoc_types[0].iter().map(|(_, param)| param);
+        Some(quote! {
+            ::std::boxed::Box<dyn #trait_path<#(#trait_args,)* #(#assoc_names=#assoc_params),*>>
+        })
+    } else {
+        None
+    };
+
+    let mock_generics = MockGenerics {
+        lifetimes: &lifetimes,
+        type_params: &type_params,
+        const_params: &const_params,
+        where_clause: where_clause.as_ref(),
+    };
+    let struct_item = generate_mock_struct(
+        &vis,
+        &mock_ident,
+        &mock_generics,
+        &opts.derives,
+        delegate_type.as_ref(),
+    );
 
     // Generic parameters used for impls. It is part inside angles in
-    // `impl<A: ::std::fmt::Debug, B: ::std::fmt::Debug, ...> ...`.
-    let generics = {
+    // `impl<A: Clone + ::std::fmt::Debug, B: ::std::fmt::Debug, ...> ...`.
+    // Bounds declared in trait are kept, `Debug` bound is added unless
+    // `no_debug_bound` option is given.
+    let debug_bound = !opts.no_debug_bound;
+    let make_generics = |debug_bound: bool| {
         let mut gen = Generics::default();
-        gen.params = assoc_types
+        let debug_param_bound: TypeParamBound = parse_quote! { ::std::fmt::Debug };
+        let type_generic_params = trait_type_params
             .iter()
+            .chain(assoc_type_params.iter())
             .cloned()
-            .map(|param| -> GenericParam {
-                parse_quote! { #param: ::std::fmt::Debug }
-            })
+            .map(|mut param: TypeParam| -> GenericParam {
+                if debug_bound {
+                    param.bounds.push(debug_param_bound.clone());
+                }
+                GenericParam::Type(param)
+            });
+        gen.params = lifetimes
+            .iter()
+            .map(|lifetime| -> GenericParam { parse_quote! { #lifetime } })
+            .chain(type_generic_params)
+            .chain(const_params.iter().cloned().map(GenericParam::Const))
             .collect();
         gen
     };
-    // Type of mock struct with all type parameters specified.
+    let generics = make_generics(debug_bound);
+    // Type of mock struct with all lifetime and type parameters specified.
     let struct_path: Path = {
-        let assoc_types = &assoc_types;
-        parse_quote! { #mock_ident<#(#assoc_types),*> }
+        let lifetimes = &lifetimes;
+        let type_params = &type_params;
+        let const_args = &const_args;
+        parse_quote! { #mock_ident<#(#lifetimes,)* #(#type_params,)* #(#const_args),*> }
     };
     let struct_type: Type = parse_quote! { #struct_path };
 
-    let mut generated_items = vec![struct_item];
+    let mut generated_items = if extend { Vec::new() } else { vec![struct_item] };
     let mut has_static_methods = false;
-    let mut mock_type_ids = Punctuated::<usize, Token![,]>::new();
+    let mut mock_type_ids = Punctuated::<TokenStream, Token![,]>::new();
+
+    for (&(ref trait_path, item_trait), assoc_type_pairs) in traits.iter().zip(&trait_assoc_types) {
+        // When mock implements several traits, they may have methods with
+        // the same name, so method names used in messages are qualified with
+        // trait path, e.g. `A+B#0.B::foo()`.
+        let method_name_prefix = if traits.len() > 1 || extend {
+            let trait_name = trait_path.segments.iter().map(|s| s.ident.to_string()).join("::");
+            format!("{}::", trait_name)
+        } else {
+            String::new()
+        };
+
+        // Trait path with type arguments, e.g. `Repository<T>`.
+        let trait_path = &path_with_generic_args(trait_path, &item_trait.generics);
 
-    for &(ref trait_path, ref members) in &traits {
         let mut impl_methods = Vec::new();
         let mut trait_impl_methods = Vec::new();
 
         let mut static_impl_methods = Vec::new();
         let mut static_trait_impl_methods = Vec::new();
 
-        let mock_type_id = unsafe {
-            let id = NEXT_MOCK_TYPE_ID;
-            NEXT_MOCK_TYPE_ID += 1;
-            id
+        let mock_type_key = format!("{}#{}", mock_ident, trait_path.into_token_stream());
+        let mock_type_id = mock_type_id_expr(&mock_type_key);
+        let mock_type_name = mock_type_name_expr(&mock_type_key);
+        mock_type_ids.push(quote! { (#mock_type_id, #mock_type_name) });
+        let ctx = MethodsContext {
+            mock_type_id: &mock_type_id,
+            debug_bound,
+            default_returns: opts.default_returns,
+            opaque_call_match: opts.opaque_call_match,
+        };
+        let mocked_trait = MockedTrait {
+            path: trait_path,
+            assoc_types: assoc_type_pairs,
+            mock_struct_path: &struct_path,
         };
-        mock_type_ids.push(mock_type_id);
 
-        for member in members.iter() {
+        for member in item_trait.items.iter() {
             match member {
-                TraitItem::Method(TraitItemMethod { ref sig, .. }) => {
-                    if sig.unsafety.is_some() {
-                        return Err("unsafe trait methods are not supported".to_string());
+                TraitItem::Method(TraitItemMethod {
+                    ref attrs,
+                    ref sig,
+                    ref default,
+                    ..
+                }) => {
+                    // Skipped methods aren't implemented by mock at all, so default
+                    // implementation from trait is used.
+                    if opts.skip.contains(&sig.ident) {
+                        if default.is_none() {
+                            return Err(Error::new_spanned(
+                                &sig.ident,
+                                "only methods with default implementation may be skipped",
+                            ));
+                        }
+                        continue;
                     }
-                    if sig.constness.is_some() {
-                        return Err("const trait methods are not supported".to_string());
+                    if let Some(ref unsafety) = sig.unsafety {
+                        return Err(Error::new_spanned(
+                            unsafety,
+                            "unsafe trait methods are not supported",
+                        ));
                     }
-                    if sig.abi != None {
-                        return Err("non-Rust ABIs for trait methods are not supported".to_string());
+                    // `const` qualifier is ignored: mocked method calls into scenario,
+                    // so it can't be const, generated implementation is ordinary method.
+                    if let Some(ref abi) = sig.abi {
+                        return Err(Error::new_spanned(
+                            abi,
+                            "non-Rust ABIs for trait methods are not supported",
+                        ));
                     }
 
+                    // Conditionally compiled methods are implemented by mock
+                    // under the same conditions.
+                    let cfg_attrs: Vec<&Attribute> = attrs.iter().filter(|a| is_cfg_attr(a)).collect();
+                    // Documentation of trait method is copied to `*_call` and
+                    // `expect_*` methods, so that it is shown by IDE.
+                    let impl_method_attrs: Vec<&Attribute> = attrs
+                        .iter()
+                        .filter(|a| is_cfg_attr(a) || is_doc_attr(a))
+                        .collect();
+
+                    let method_name = format!("{}{}", method_name_prefix, unraw(&sig.ident));
+                    let stub_opts = StubOptions {
+                        default_body: if opts.delegate_defaults {
+                            default.as_ref()
+                        } else {
+                            None
+                        },
+                        delegate_calls: opts.spy,
+                        ..Default::default()
+                    };
                     let methods = generate_trait_methods(
-                        sig.ident.clone(),
-                        &sig.decl,
-                        &sig.decl.generics,
-                        &trait_path,
-                        mock_type_id,
-                        &struct_path,
+                        &ctx,
+                        &mocked_trait,
+                        sig,
+                        &method_name,
+                        stub_opts,
+                        &impl_method_attrs,
                     )?;
-                    if methods.is_static {
-                        static_impl_methods.push(methods.impl_method);
-                        static_trait_impl_methods.push(methods.trait_impl_method);
+
+                    let GeneratedMethods {
+                        impl_method,
+                        trait_impl_method,
+                        is_static,
+                    } = methods;
+                    // Attributes requested with `keep_attrs` are kept on trait
+                    // implementation methods, so that mock behaves like real implementation.
+                    let kept_attrs: Vec<&Attribute> = attrs
+                        .iter()
+                        .filter(|a| is_kept_attr(a, &opts.keep_attrs))
+                        .collect();
+                    let trait_impl_method = quote! {
+                        #(#cfg_attrs)* #(#kept_attrs)* #trait_impl_method
+                    };
+                    if is_static {
+                        static_impl_methods.push(impl_method);
+                        static_trait_impl_methods.push(trait_impl_method);
                     } else {
-                        impl_methods.push(methods.impl_method);
-                        trait_impl_methods.push(methods.trait_impl_method);
-                    }
-                }
-                TraitItem::Type(TraitItemType { ref bounds, .. }) => {
-                    if !bounds.is_empty() {
-                        return Err("associated type bounds are not supported yet".to_string());
+                        impl_methods.push(impl_method);
+                        trait_impl_methods.push(trait_impl_method);
                     }
                 }
+                TraitItem::Type(..) => {}
                 TraitItem::Const(..) => {
-                    return Err("trait constants are not supported yet".to_string());
+                    return Err(Error::new_spanned(
+                        member,
+                        "trait constants are not supported yet",
+                    ));
                 }
                 TraitItem::Macro(..) => {
-                    return Err("trait macros are not supported yet".to_string());
+                    return Err(Error::new_spanned(member, "trait macros are not supported yet"));
                 }
                 TraitItem::Verbatim(..) => {
-                    return Err("vertatim trait items are not supported".to_string());
+                    return Err(Error::new_spanned(
+                        member,
+                        "vertatim trait items are not supported",
+                    ));
                 }
             }
         }
 
+        // With `no_static_mock` option expectations for static methods are
+        // created using main mock, which is registered for them instead of
+        // static mock.
+        if opts.no_static_mock && !static_impl_methods.is_empty() {
+            has_static_methods = true;
+            impl_methods.append(&mut static_impl_methods);
+        }
+
         // `impl<...> AMock<...> { pub fn foo_call(...) { ... } }`
         let impl_item = quote! {
-            impl #generics #struct_type {
+            impl #generics #struct_type #where_clause {
                 #(#impl_methods)*
             }
         };
 
         // `impl<...> A for AMock<...> { ... }`
+        // Implementation of unsafe trait must be marked unsafe too.
+        let unsafety = item_trait.unsafety;
         let trait_impl_items = trait_impl_methods;
-        let trait_type_items = assoc_types
+        let trait_type_items = assoc_type_pairs
             .iter()
-            .cloned()
-            .zip(assoc_types.iter().cloned())
             .map(|(assoc, param)| -> ImplItemType {
                 let path: Path = parse_quote! { #param };
                 parse_quote! { type #assoc = #path; }
             });
         let trait_impl_item = quote! {
-            impl #generics #trait_path for #struct_type {
+            #unsafety impl #generics #trait_path for #struct_type #where_clause {
                 #(#trait_type_items)*
                 #(#trait_impl_items)*
                 #(#static_trait_impl_methods)*
@@ -404,34 +789,38 @@ fn generate_mock_for_traits(
         if !static_impl_methods.is_empty() {
             has_static_methods = true;
 
-            let static_mock_name = format!("{}Static", mock_ident);
-            let static_mock_ident = Ident::new(&static_mock_name.clone(), Span::call_site());
-            let static_struct_item = generate_mock_struct(&static_mock_ident, &assoc_types);
-            let static_struct_type: Type = parse_quote! { #static_mock_ident<#(assoc_types),*> };
+            let static_mock_ident = opts.static_mock_name.clone().unwrap_or_else(|| {
+                Ident::new(&format!("{}Static", mock_ident), Span::call_site())
+            });
+            let static_mock_name = static_mock_ident.to_string();
+            let static_struct_item =
+                generate_mock_struct(&vis, &static_mock_ident, &mock_generics, &[], None);
+            let (lifetimes, type_params, const_args) = (&lifetimes, &type_params, &const_args);
+            let static_struct_type: Type = parse_quote! {
+                #static_mock_ident<#(#lifetimes,)* #(#type_params,)* #(#const_args),*>
+            };
 
             // `impl<...> AMockStatic<...> { pub fn foo_call(...) { ... } }`
             let static_impl_item = quote! {
-                impl #generics #static_struct_type {
+                impl #generics #static_struct_type #where_clause {
                     #(#static_impl_methods)*
                 }
             };
 
             let custom_init_code = quote! {
-                ::mockers::EXTERN_MOCKS.with(|mocks| {
-                    let mut mocks = mocks.borrow_mut();
-                    for mock_type_id in &[#mock_type_ids] {
-                        if mocks.contains_key(mock_type_id) {
-                            panic!("Mock {} for static methods already exists", #static_mock_name);
-                        }
-                        mocks.insert(*mock_type_id, (id, scenario_int.clone()));
+                for &(mock_type_id, mock_type_name) in &[#mock_type_ids] {
+                    if !::mockers::register_extern_mock(mock_type_id, mock_type_name, id, &scenario_int) {
+                        panic!("Mock {} for static methods already exists", #static_mock_name);
                     }
-                });
+                }
             };
+            let test_only_guard = test_only_guard(opts, &static_mock_name);
             let static_mock_impl = generate_mock_impl(
                 &static_mock_ident,
                 &static_mock_name,
-                &assoc_types,
-                &custom_init_code,
+                &mock_generics,
+                &quote! { #test_only_guard #custom_init_code },
+                None,
             );
 
             generated_items.push(static_struct_item);
@@ -440,22 +829,77 @@ fn generate_mock_for_traits(
         }
     }
 
+    // Extension only implements traits, everything else is generated by
+    // previous `mock!` invocation.
+    if extend {
+        if has_static_methods {
+            return Err(Error::new_spanned(
+                &mock_ident,
+                "traits with static methods can't be added to existing mock",
+            ));
+        }
+        return Ok(quote! { #(#generated_items)* });
+    }
+
     let mocked_class_name = traits
         .iter()
-        .map(|&(ref path, _)| {
+        .map(|(path, _)| {
             let mut tokens = TokenStream::new();
             path.to_tokens(&mut tokens);
             tokens.to_string()
         })
         .join("+");
 
-    let mock_impl_item =
-        generate_mock_impl(&mock_ident, &mocked_class_name, &assoc_types, &quote! {});
+    // Several mocks may be created with `no_static_mock` option, e.g. one
+    // of them may be returned from mocked constructor. Static methods are
+    // routed to the first one, but mocks from another scenario conflict.
+    let custom_init_code = if opts.no_static_mock && has_static_methods {
+        let mock_name_str = mock_ident.to_string();
+        quote! {
+            for &(mock_type_id, mock_type_name) in &[#mock_type_ids] {
+                if !::mockers::register_extern_mock(mock_type_id, mock_type_name, id, &scenario_int) {
+                    let (_, registered_scenario) = ::mockers::get_extern_mock(mock_type_id);
+                    if !::std::rc::Rc::ptr_eq(&registered_scenario, &scenario_int) {
+                        panic!("Mock {} for static methods already exists", #mock_name_str);
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let test_only_guard = test_only_guard(opts, &mock_ident.to_string());
+    let mock_impl_item = generate_mock_impl(
+        &mock_ident,
+        &mocked_class_name,
+        &mock_generics,
+        &quote! { #test_only_guard #custom_init_code },
+        delegate_type.as_ref(),
+    );
     generated_items.push(mock_impl_item);
 
-    let assoc_types_ref = &assoc_types;
+    if let Some(ref delegate_type) = delegate_type {
+        generated_items.push(quote! {
+            impl #generics #struct_type #where_clause {
+                /// Creates mock which delegates calls of methods having no
+                /// expectations to given implementation.
+                #[allow(dead_code)]
+                pub fn spy(scenario: &::mockers::Scenario, delegate: #delegate_type) -> Self {
+                    let mut mock = scenario.create_mock::<Self>();
+                    mock.delegate = ::std::option::Option::Some(delegate);
+                    mock
+                }
+            }
+        });
+    }
+
+    let lifetimes_ref = &lifetimes;
+    let type_params_ref = &type_params;
+    let const_params_ref = &const_params;
+    let const_args_ref = &const_args;
     let debug_impl_item = quote! {
-        impl<#(#assoc_types_ref),*> ::std::fmt::Debug for #mock_ident_ref<#(#assoc_types_ref),*> {
+        impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*> ::std::fmt::Debug
+            for #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {
             fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
                 f.write_str(self.scenario.borrow().get_mock_name(self.mock_id))
             }
@@ -463,55 +907,347 @@ fn generate_mock_for_traits(
     };
     generated_items.push(debug_impl_item);
 
-    let has_generic_method = Itertools::flatten(traits.iter().map(|&(_, members)| members.iter()))
+    // Inherent method would shadow trait method with the same name, so
+    // `expectations_satisfied`, `detached`, `reset`, `with_name`, `mock_id`,
+    // `unmet_expectations`, `weak` and `into_boxed` aren't generated in this case.
+    let has_trait_method = |name: &str| {
+        Itertools::flatten(traits.iter().map(|&(_, t)| t.items.iter())).any(|member| match member {
+            TraitItem::Method(TraitItemMethod { ref sig, .. }) => sig.ident == name,
+            _ => false,
+        })
+    };
+    if !has_trait_method("expectations_satisfied") {
+        generated_items.push(quote! {
+            impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*>
+                #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {
+                /// Returns whether all expectations set for this mock are satisfied.
+                #[allow(dead_code)]
+                pub fn expectations_satisfied(&self) -> bool {
+                    self.scenario.borrow().expectations_satisfied_for(self.mock_id)
+                }
+            }
+        });
+    }
+    if !has_trait_method("detached") {
+        generated_items.push(quote! {
+            impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*>
+                #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {
+                /// Creates mock which isn't attached to any scenario, any call to it panics.
+                /// Use it in tests which need mock instance only to satisfy type checks.
+                #[allow(dead_code)]
+                pub fn detached() -> Self {
+                    ::mockers::detached_mock()
+                }
+            }
+        });
+    }
+    if !has_trait_method("with_name") {
+        generated_items.push(quote! {
+            impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*>
+                #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {
+                /// Creates mock with given name, which is used by `Debug` and
+                /// in error messages instead of generated one.
+                #[allow(dead_code)]
+                pub fn with_name(scenario: &::mockers::Scenario, name: &str) -> Self {
+                    scenario.create_named_mock::<Self>(::std::borrow::ToOwned::to_owned(name))
+                }
+            }
+        });
+    }
+    if !has_trait_method("mock_id") {
+        generated_items.push(quote! {
+            impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*>
+                #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {
+                /// Returns ID of this mock in scenario, it is given to `CallMatchN`
+                /// constructors and `ScenarioInternals` methods.
+                #[allow(dead_code)]
+                pub fn mock_id(&self) -> usize {
+                    self.mock_id
+                }
+            }
+        });
+    }
+    if !has_trait_method("reset") {
+        generated_items.push(quote! {
+            impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*>
+                #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {
+                /// Removes all expectations set for this mock, so that it may be
+                /// reused with new expectations.
+                #[allow(dead_code)]
+                pub fn reset(&self) {
+                    self.scenario.borrow_mut().reset_expectations_for(self.mock_id);
+                }
+            }
+        });
+    }
+    if !has_trait_method("unmet_expectations") {
+        generated_items.push(quote! {
+            impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*>
+                #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {
+                /// Returns expectations set for this mock which aren't satisfied yet.
+                #[allow(dead_code)]
+                pub fn unmet_expectations(&self) -> ::std::vec::Vec<::mockers::UnmetExpectation> {
+                    self.scenario.borrow().unmet_expectations_for(self.mock_id)
+                }
+            }
+        });
+    }
+
+    // Clones refer to the same mock ID, so they share expectations.
+    if opts.clone {
+        let phantom_data_initializers = (0..lifetimes.len() + type_params.len())
+            .map(|_| quote! { ::std::marker::PhantomData });
+        let clone_impl_item = quote! {
+            impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*> ::std::clone::Clone
+                for #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {
+                fn clone(&self) -> Self {
+                    #mock_ident_ref {
+                        scenario: ::std::clone::Clone::clone(&self.scenario),
+                        mock_id: self.mock_id,
+                        _phantom_data: (#(#phantom_data_initializers),*),
+                    }
+                }
+            }
+        };
+        generated_items.push(clone_impl_item);
+
+        // Weak handle refers to the same mock ID too, but doesn't keep
+        // scenario alive.
+        if !has_trait_method("weak") {
+            let weak_impl_item = quote! {
+                impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*>
+                    #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {
+                    #[allow(dead_code)]
+                    pub fn weak(&self) -> ::mockers::WeakMock<Self> {
+                        ::mockers::WeakMock::new(self.mock_id, ::std::rc::Rc::downgrade(&self.scenario))
+                    }
+                }
+            };
+            generated_items.push(weak_impl_item);
+        }
+    }
+
+    // Mock holds `Rc` to scenario internals, so these impls are only sound
+    // when user guarantees there is no concurrent access.
+    if opts.unsafe_send_sync {
+        generated_items.push(quote! {
+            unsafe impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*> ::std::marker::Send
+                for #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {}
+            unsafe impl<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*> ::std::marker::Sync
+                for #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {}
+        });
+    }
+
+    let not_object_safe = Itertools::flatten(traits.iter().map(|&(_, t)| t.items.iter()))
         .any(|member| match member {
             TraitItem::Method(TraitItemMethod { ref sig, .. }) => {
                 !sig.decl.generics.params.is_empty()
+                    || sig.asyncness.is_some()
+                    || returns_impl_trait(&sig.decl.output)
+                    || sig.decl.inputs.iter().any(|arg| {
+                        matches!(
+                            *arg,
+                            FnArg::Captured(ArgCaptured { ty: Type::ImplTrait(..), .. })
+                                | FnArg::Ignored(Type::ImplTrait(..))
+                        )
+                    })
+                    || (mentions_self_in_signature(&sig.decl)
+                        && !sig
+                            .decl
+                            .generics
+                            .where_clause
+                            .iter()
+                            .flat_map(|w| w.predicates.iter())
+                            .any(is_self_predicate))
             }
             _ => false,
         });
-    if local && !has_generic_method && !has_static_methods {
-        let (ref trait_path, _) = traits[traits.len() - 1];
+    // Delegate of spy mock is trait object.
+    if opts.spy && (not_object_safe || has_static_methods) {
+        return Err(Error::new_spanned(
+            &traits[0].1.ident,
+            "spy option requires trait which can be made into object",
+        ));
+    }
 
-        // Create path for trait being mocked. Path includes bindings for all associated types.
+    // `Mocked` is implemented for `&'static dyn Trait`, but traits with generic,
+    // async or static methods can't be made into objects, so there is nothing to
+    // implement it for. `create_mock_for` isn't available for such traits,
+    // `Mocked` has `diagnostic::on_unimplemented` message telling user to use
+    // `create_mock` instead.
+    // Associated types with the same name in several traits can't be bound
+    // in trait object type unambiguously, so `Mocked` isn't implemented either.
+    if local && !not_object_safe && !has_static_methods && !has_assoc_type_collisions {
+        let (ref trait_path, item_trait) = traits[traits.len() - 1];
+
+        // Create path for trait being mocked. Path includes lifetime and type
+        // arguments and bindings for all associated types.
         // Generated impl example:
         //
-        //     impl<Item> ::mockers::Mocked for &'static A<Item=Item> {
-        //         type MockImpl = AMock<Item>;
+        //     impl<'a, T, Item> ::mockers::Mocked for &'static dyn A<'a, T, Item=Item> {
+        //         type MockImpl = AMock<'a, T, Item>;
         //     }
-        let assoc_types_ref2 = assoc_types_ref;
+        //
+        // Trait object type is only valid when parameters satisfy bounds
+        // declared in trait, so they are kept. `A` and `dyn A` denote the same
+        // type, so both `create_mock_for::<A>` and `create_mock_for::<dyn A>` work.
+        let mocked_generics = make_generics(false);
+        let trait_args = generic_args(&item_trait.generics);
+        let assoc_pairs: Vec<&(Ident, Ident)> =
+            trait_assoc_types.iter().flat_map(|pairs| pairs.iter()).collect();
+        let assoc_names = assoc_pairs.iter().map(|&(assoc, _)| assoc);
+        let assoc_params = assoc_pairs.iter().map(|&(_, param)| param);
+        let trait_object_type = quote! {
+            dyn #trait_path<#(#trait_args,)* #(#assoc_names=#assoc_params),*>
+        };
         let mocked_impl_item = quote! {
-            impl<#(#assoc_types_ref),*> ::mockers::Mocked
-                for &'static #trait_path<#(#assoc_types_ref=#assoc_types_ref2),*> {
-                type MockImpl = #mock_ident_ref<#(#assoc_types_ref),*>;
+            impl #mocked_generics ::mockers::Mocked
+                for &'static #trait_object_type
+                #where_clause {
+                type MockImpl = #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*>;
             }
         };
 
-        generated_items.push(mocked_impl_item)
+        // `MockImpl` would expose mock type, which can't be less visible than
+        // public trait, so `create_mock` must be used for such mock.
+        let is_public = |vis: &Visibility| matches!(*vis, Visibility::Public(..));
+        if is_public(&vis) || !is_public(&item_trait.vis) {
+            generated_items.push(mocked_impl_item);
+        }
+
+        // Mock implements trait only when parameters have `Debug` bound
+        // (unless `no_debug_bound` is given), so the same generics are used.
+        if !has_trait_method("into_boxed") {
+            generated_items.push(quote! {
+                impl #generics
+                    #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*> #where_clause {
+                    /// Boxes mock into trait object, so that it may be passed to code
+                    /// accepting `Box<dyn Trait>` without explicit cast.
+                    #[allow(dead_code)]
+                    pub fn into_boxed(self) -> ::std::boxed::Box<#trait_object_type>
+                    where
+                        Self: 'static,
+                    {
+                        ::std::boxed::Box::new(self)
+                    }
+                }
+            });
+        }
+    }
+
+    // Type alias naming mock by trait name, e.g. `type AMockImpl = AMock;`.
+    // Unlike `<&'static A as Mocked>::MockImpl`, it is available even for
+    // traits which can't be made into objects.
+    if local {
+        let (_, item_trait) = traits[traits.len() - 1];
+        let alias_ident = Ident::new(&format!("{}MockImpl", unraw(&item_trait.ident)), Span::call_site());
+        if alias_ident != mock_ident {
+            generated_items.push(quote! {
+                #[allow(dead_code)]
+                #vis type #alias_ident<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_params_ref),*> =
+                    #mock_ident_ref<#(#lifetimes_ref,)* #(#type_params_ref,)* #(#const_args_ref),*>;
+            });
+        }
     }
 
     Ok(quote! { #(#generated_items)* })
 }
 
+/// Returns copy of `path` with lifetime and type parameters from `generics` added
+/// as arguments to the last segment, e.g. `Repository` + `<'a, T>` gives
+/// `Repository<'a, T>`.
+fn path_with_generic_args(path: &Path, generics: &Generics) -> Path {
+    let args = generic_args(generics);
+    let mut path = path.clone();
+    if !args.is_empty() {
+        let last = path.segments.len() - 1;
+        path.segments[last].arguments = PathArguments::AngleBracketed(
+            parse_quote! { <#(#args),*> },
+        );
+    }
+    path
+}
+
+/// Returns arguments corresponding to parameters declared in `generics`, in
+/// the same order, e.g. `'a`, `T`, `N` for `<'a, T, const N: usize>`.
+fn generic_args(generics: &Generics) -> Vec<TokenStream> {
+    generics
+        .params
+        .iter()
+        .map(|param| match *param {
+            GenericParam::Lifetime(ref def) => {
+                let lifetime = &def.lifetime;
+                quote! { #lifetime }
+            }
+            GenericParam::Type(ref param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+            GenericParam::Const(ref param) => {
+                let ident = &param.ident;
+                quote! { #ident }
+            }
+        })
+        .collect()
+}
+
+/// Generic parameters of mock struct.
+#[derive(Clone, Copy, Default)]
+struct MockGenerics<'a> {
+    lifetimes: &'a [Lifetime],
+    /// Type parameters of mocked trait followed by associated types.
+    type_params: &'a [Ident],
+    const_params: &'a [ConstParam],
+    where_clause: Option<&'a WhereClause>,
+}
+
 /// Create mock structure. Structure is quite simple and basically contains only reference
 /// to scenario and own ID.
-/// Associated types of original trait are converted to type parameters.
-/// Since type parameters are unused, we have to use PhantomData for each of them.
+/// Lifetime, type and const parameters of original trait are passed through to mock struct,
+/// and associated types of original trait are converted to type parameters too.
+/// `where` clause of mocked traits is copied to mock struct.
+/// Since parameters are unused, we have to use PhantomData for each of them.
 /// We use tuple of |PhantomData| to create just one struct field.
-fn generate_mock_struct(mock_ident: &Ident, associated_type_idents: &[Ident]) -> TokenStream {
-    let phantom_types: Vec<_> = associated_type_idents
+fn generate_mock_struct(
+    vis: &Visibility,
+    mock_ident: &Ident,
+    mock_generics: &MockGenerics,
+    derives: &[Ident],
+    delegate_type: Option<&TokenStream>,
+) -> TokenStream {
+    let MockGenerics {
+        lifetimes,
+        type_params: associated_type_idents,
+        const_params,
+        where_clause,
+    } = *mock_generics;
+    // `Debug` is always implemented for mock manually.
+    let derives: Vec<&Ident> = derives.iter().filter(|d| *d != "Debug").collect();
+    let derive_attr = if derives.is_empty() {
+        None
+    } else {
+        Some(quote! { #[derive(#(#derives),*)] })
+    };
+    let phantom_types: Vec<_> = lifetimes
         .iter()
-        .map(|ty_param| {
-            quote! { ::std::marker::PhantomData<#ty_param> }
+        .map(|lifetime| {
+            quote! { ::std::marker::PhantomData<&#lifetime ()> }
         })
+        .chain(associated_type_idents.iter().map(|ty_param| {
+            quote! { ::std::marker::PhantomData<#ty_param> }
+        }))
         .collect();
     let phantom_tuple_type = quote! { (#(#phantom_types),*) };
+    let delegate_field = delegate_type.map(|ty| quote! { delegate: ::std::option::Option<#ty>, });
 
     quote! {
-        pub struct #mock_ident<#(#associated_type_idents),*> {
+        #derive_attr
+        #vis struct #mock_ident<#(#lifetimes,)* #(#associated_type_idents,)* #(#const_params),*>
+            #where_clause {
             scenario: ::std::rc::Rc<::std::cell::RefCell<::mockers::ScenarioInternals>>,
             mock_id: usize,
             _phantom_data: #phantom_tuple_type,
+            #delegate_field
         }
     }
 }
@@ -519,23 +1255,34 @@ fn generate_mock_struct(mock_ident: &Ident, associated_type_idents: &[Ident]) ->
 fn generate_mock_impl(
     mock_ident: &Ident,
     mocked_class_name: &str,
-    associated_type_idents: &[Ident],
+    mock_generics: &MockGenerics,
     custom_init_code: &TokenStream,
+    delegate_type: Option<&TokenStream>,
 ) -> TokenStream {
-    let phantom_data_initializers: Vec<_> = associated_type_idents
-        .iter()
+    let MockGenerics {
+        lifetimes,
+        type_params: associated_type_idents,
+        const_params,
+        where_clause,
+    } = *mock_generics;
+    let const_args = const_params.iter().map(|p| &p.ident);
+    let delegate_init = delegate_type.map(|_| quote! { delegate: ::std::option::Option::None, });
+    let phantom_data_initializers: Vec<_> = (0..lifetimes.len() + associated_type_idents.len())
         .map(|_| {
             quote! { ::std::marker::PhantomData }
         })
         .collect();
     quote! {
-        impl<#(#associated_type_idents),*> ::mockers::Mock for #mock_ident<#(#associated_type_idents),*> {
+        impl<#(#lifetimes,)* #(#associated_type_idents,)* #(#const_params),*> ::mockers::Mock
+            for #mock_ident<#(#lifetimes,)* #(#associated_type_idents,)* #(#const_args),*>
+            #where_clause {
             fn new(id: usize, scenario_int: ::std::rc::Rc<::std::cell::RefCell<::mockers::ScenarioInternals>>) -> Self {
                 #custom_init_code
                 #mock_ident {
                     scenario: scenario_int,
                     mock_id: id,
                     _phantom_data: (#(#phantom_data_initializers),*),
+                    #delegate_init
                 }
             }
 
@@ -546,23 +1293,99 @@ fn generate_mock_impl(
     }
 }
 
+/// With `test_only` option, mock creation panics unless crate defining mock
+/// is compiled for tests, so that mocks don't leak into production code.
+fn test_only_guard(opts: &MockAttrOptions, mock_name: &str) -> TokenStream {
+    if opts.test_only {
+        quote! {
+            if !cfg!(test) {
+                panic!("Mock {} may be created in tests only", #mock_name);
+            }
+        }
+    } else {
+        quote! {}
+    }
+}
+
 struct GeneratedMethods {
     trait_impl_method: TokenStream,
     impl_method: TokenStream,
     is_static: bool,
 }
 
+/// Settings shared by code generated for all methods of one mock.
+struct MethodsContext<'a> {
+    /// Expression evaluating to mock type ID.
+    mock_type_id: &'a TokenStream,
+    /// Add `Debug` bound to type parameters of generic methods.
+    debug_bound: bool,
+    /// Return default value from methods without expectations.
+    default_returns: bool,
+    /// Return `impl CallMatchReactions` from `*_call` methods.
+    opaque_call_match: bool,
+}
+
+impl<'a> MethodsContext<'a> {
+    /// Context for mocks of free functions, which have no options.
+    fn for_functions(mock_type_id: &'a TokenStream) -> Self {
+        MethodsContext {
+            mock_type_id,
+            debug_bound: true,
+            default_returns: false,
+            opaque_call_match: false,
+        }
+    }
+}
+
+/// Mocked method signature. `self` argument isn't included into `args`, and
+/// `return_type` of async method is type of future output.
+#[derive(Clone, Copy)]
+struct MockedMethod<'a> {
+    ident: &'a Ident,
+    /// Method name used in messages.
+    name: &'a str,
+    generics: &'a Generics,
+    args: &'a Punctuated<FnArg, Token![,]>,
+    return_type: &'a Type,
+    is_async: bool,
+}
+
+/// Mocked trait as seen from mock struct implementing it.
+struct MockedTrait<'a> {
+    /// Trait path with type arguments, e.g. `Repository<T>`.
+    path: &'a Path,
+    /// Associated types of trait paired with mock type parameters.
+    assoc_types: &'a [(Ident, Ident)],
+    /// Type of mock struct with all parameters specified.
+    mock_struct_path: &'a Path,
+}
+
+/// Properties of stub, i.e. function or method which replaces mocked one.
+#[derive(Clone, Copy, Default)]
+struct StubOptions<'a> {
+    vis: Option<&'a Visibility>,
+    is_unsafe: bool,
+    abi: Option<&'a Abi>,
+    is_variadic: bool,
+    /// Default implementation called when there are no expectations for method.
+    default_body: Option<&'a Block>,
+    /// Delegate calls of method without expectations to spied implementation.
+    delegate_calls: bool,
+}
+
 fn generate_trait_methods(
-    method_ident: Ident,
-    decl: &FnDecl,
-    generics: &Generics,
-    trait_path: &Path,
-    mock_type_id: usize,
-    mock_struct_path: &Path,
-) -> Result<GeneratedMethods, String> {
+    ctx: &MethodsContext,
+    mocked_trait: &MockedTrait,
+    sig: &MethodSig,
+    method_name: &str,
+    stub_opts: StubOptions,
+    impl_method_attrs: &[&Attribute],
+) -> Result<GeneratedMethods, Error> {
+    let decl = &sig.decl;
+    let generics = &decl.generics;
     let is_static = match decl.inputs.iter().next() {
-        Some(FnArg::SelfRef(..)) | Some(FnArg::SelfValue(..)) => false,
-        _ => true,
+        Some(arg) => !is_self_arg(arg),
+        None => true,
     };
 
     let return_type = match decl.output {
@@ -570,6 +1393,17 @@ fn generate_trait_methods(
         ReturnType::Type(_, ref ty) => *ty.clone(),
     };
 
+    // Async methods and methods returning `impl Future<Output = T>` are
+    // mocked as if they return `T`, and stub wraps result into ready future.
+    let (return_type, is_async) = if sig.asyncness.is_some() {
+        (return_type, true)
+    } else {
+        match future_output_type(&return_type) {
+            Some(output_type) => (output_type, true),
+            None => (return_type, false),
+        }
+    };
+
     if is_static {
         // Let imagine we have
         // trait A {
@@ -577,30 +1411,34 @@ fn generate_trait_methods(
         //     fn foo(&self);
         // }
         // Implementation of method `new` goes to `AMockStatic`, but `Self` must be
-        // resolved to `AMock`.
-        let adjusted_return_type = set_self(&return_type, mock_struct_path);
-        let mock_method = generate_impl_method(
-            mock_type_id,
-            method_ident.clone(),
-            &generics,
-            &decl.inputs,
-            &adjusted_return_type,
-        )?;
-
-        let get_info_expr = quote! {
-            ::mockers::EXTERN_MOCKS.with(|mocks| {
-                mocks.borrow().get(&#mock_type_id).expect("Mock instance not found").clone()
-            })
+        // resolved to `AMock` with all its type parameters.
+        let adjusted_return_type = set_self(
+            &return_type,
+            mocked_trait.mock_struct_path,
+            mocked_trait.path,
+            mocked_trait.assoc_types,
+        );
+        let method = MockedMethod {
+            ident: &sig.ident,
+            name: method_name,
+            generics,
+            args: &decl.inputs,
+            return_type: &adjusted_return_type,
+            is_async,
         };
+        let mock_method = generate_impl_method(ctx, &method, impl_method_attrs)?;
+
+        let mock_type_id = ctx.mock_type_id;
+        let get_info_expr = quote! { ::mockers::get_extern_mock(#mock_type_id) };
         let stub_method = generate_stub_code(
-            mock_type_id,
-            &method_ident,
-            &generics,
+            ctx,
+            &method,
             None,
             get_info_expr,
-            &decl.inputs,
-            &adjusted_return_type,
-            false,
+            StubOptions {
+                delegate_calls: false,
+                ..stub_opts
+            },
         )?;
 
         return Ok(GeneratedMethods {
@@ -612,34 +1450,46 @@ fn generate_trait_methods(
 
     // Arguments without `&self`.
     let self_arg = &decl.inputs[0];
-    let args = Punctuated::from_iter(decl.inputs.iter().cloned().skip(1));
+    let args = Punctuated::from_iter(decl.inputs.iter().skip(1).cloned());
 
-    let trait_impl_method = generate_trait_impl_method(
-        mock_type_id,
-        method_ident.clone(),
+    // Methods bound by `Self: Sized` can't be called on delegate trait object.
+    let delegate_calls = stub_opts.delegate_calls
+        && !generics
+            .where_clause
+            .iter()
+            .flat_map(|w| w.predicates.iter())
+            .any(is_self_predicate);
+
+    let method = MockedMethod {
+        ident: &sig.ident,
+        name: method_name,
         generics,
+        args: &args,
+        return_type: &return_type,
+        is_async,
+    };
+    let trait_impl_method = generate_trait_impl_method(
+        ctx,
+        &method,
         self_arg,
-        &args,
-        &return_type,
-    );
+        StubOptions {
+            delegate_calls,
+            ..stub_opts
+        },
+    )?;
     let impl_method = generate_impl_method_for_trait(
-        mock_type_id,
-        method_ident,
-        generics,
-        &args,
-        &return_type,
-        trait_path,
-    );
-
-    if let (Ok(tim), Ok(im)) = (trait_impl_method, impl_method) {
-        Ok(GeneratedMethods {
-            is_static: false,
-            trait_impl_method: tim,
-            impl_method: im,
-        })
-    } else {
-        Err("failed to generate impl".to_string())
-    }
+        ctx,
+        &method,
+        mocked_trait.path,
+        mocked_trait.assoc_types,
+        impl_method_attrs,
+    )?;
+
+    Ok(GeneratedMethods {
+        is_static: false,
+        trait_impl_method,
+        impl_method,
+    })
 }
 
 /// Generate mocked trait method implementation for mock struct.
@@ -667,72 +1517,72 @@ fn generate_trait_methods(
 /// ```
 /// where constant marked with `mock_id` is unique trait method ID.
 fn generate_trait_impl_method(
-    mock_type_id: usize,
-    method_ident: Ident,
-    generics: &Generics,
+    ctx: &MethodsContext,
+    method: &MockedMethod,
     self_arg: &FnArg,
-    args: &Punctuated<FnArg, Token![,]>,
-    return_type: &Type,
-) -> Result<TokenStream, String> {
-    let get_info_expr = quote! { (self.mock_id, &self.scenario) };
-    generate_stub_code(
-        mock_type_id,
-        &method_ident,
-        generics,
-        Some(self_arg),
-        get_info_expr,
-        args,
-        return_type,
-        false,
-    )
+    stub_opts: StubOptions,
+) -> Result<TokenStream, Error> {
+    let get_info_expr = match self_arg {
+        // Method consumes `self`, so take own reference to scenario instead
+        // of borrowing it from mock which is moved into method. The same is
+        // done for typed receivers like `self: Box<Self>`.
+        FnArg::SelfValue(..) | FnArg::Captured(..) => {
+            quote! { (self.mock_id, ::std::clone::Clone::clone(&self.scenario)) }
+        }
+        _ => quote! { (self.mock_id, &self.scenario) },
+    };
+    generate_stub_code(ctx, method, Some(self_arg), get_info_expr, stub_opts)
 }
 
 fn generate_stub_code(
-    mock_type_id: usize,
-    method_ident: &Ident,
-    generics: &Generics,
+    ctx: &MethodsContext,
+    method: &MockedMethod,
     self_arg: Option<&FnArg>,
     get_info_expr: TokenStream,
-    args: &Punctuated<FnArg, Token![,]>,
-    return_type: &Type,
-    is_unsafe: bool,
-) -> Result<TokenStream, String> {
-    let method_name = method_ident.to_string();
+    stub_opts: StubOptions,
+) -> Result<TokenStream, Error> {
+    let MockedMethod {
+        ident: method_ident,
+        name: method_name,
+        generics,
+        args,
+        return_type,
+        is_async,
+    } = *method;
+    let StubOptions {
+        vis,
+        is_unsafe,
+        abi,
+        is_variadic,
+        default_body,
+        delegate_calls,
+    } = stub_opts;
+    let mock_type_id = ctx.mock_type_id;
+    check_args_count(method_ident, args)?;
+
+    // Arguments with patterns other than plain identifier are given
+    // synthesized names.
+    let arg_descs = args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| arg_ident_and_type(i, arg))
+        .collect::<Result<Vec<(Ident, Type)>, Error>>()?;
+
     // Generate expression returning tuple of all method arguments.
-    let arg_values: Vec<Expr> = args
+    // `impl Display` arguments are converted to strings.
+    let arg_values: Vec<Expr> = arg_descs
         .iter()
-        .flat_map(|i| {
-            if let &FnArg::Captured(ArgCaptured {
-                pat: Pat::Ident(PatIdent { ref ident, .. }),
-                ..
-            }) = i
-            {
-                Some(parse_quote!(#ident))
-            } else {
-                // cx.span_err(i.pat.span, "Only identifiers are accepted in argument list");
-                None
-            }
+        .map(|(ident, ty)| match erased_arg_type(ty) {
+            Some(..) => parse_quote!(::std::string::ToString::to_string(&#ident)),
+            None => parse_quote!(#ident),
         })
         .collect();
-    if arg_values.len() < args.len() {
-        return Err("".to_string());
-    }
 
     let verify_fn = Ident::new(&format!("verify{}", args.len()), Span::call_site());
 
-    let mut impl_args: Vec<FnArg> = args
+    let mut impl_args: Vec<FnArg> = arg_descs
         .iter()
-        .map(|a| {
-            let (ident, ty) = match *a {
-                FnArg::Captured(ArgCaptured {
-                    pat: Pat::Ident(PatIdent { ref ident, .. }),
-                    ref ty,
-                    ..
-                }) => (ident.clone(), ty.clone()),
-                _ => panic!("argument pattern"),
-            };
-            parse_quote! { mut #ident: #ty }
-        })
+        .map(|(ident, ty)| parse_quote! { mut #ident: #ty })
         .collect();
     if let Some(arg) = self_arg {
         impl_args.insert(0, arg.clone());
@@ -743,22 +1593,171 @@ fn generate_stub_code(
     } else {
         None
     };
+    // Functions with foreign ABI are exported under their own names, so
+    // that linker picks them up instead of real ones.
+    let no_mangle = match abi {
+        Some(Abi {
+            name: Some(ref name),
+            ..
+        }) if name.value() == "Rust" => None,
+        Some(..) => Some(quote! { #[no_mangle] }),
+        None => None,
+    };
+    // Panic about unexpected call is reported at the place where mocked
+    // method was called. It is possible for Rust ABI only.
+    let track_caller = if no_mangle.is_none() {
+        Some(quote! { #[track_caller] })
+    } else {
+        None
+    };
+    // Spy mock calls delegate for methods without expectations. Only methods
+    // with `&self` and `&mut self` receivers may be called through reference
+    // to delegate.
+    let delegate_call = match self_arg {
+        Some(FnArg::SelfRef(ArgSelfRef { ref mutability, .. })) if delegate_calls => {
+            let arg_idents = arg_descs.iter().map(|(ident, _)| ident);
+            Some(quote! {
+                if !scenario.borrow().has_expectations_for(&method_data) {
+                    if let ::std::option::Option::Some(ref #mutability delegate) = self.delegate {
+                        return delegate.#method_ident(#(#arg_idents),*);
+                    }
+                }
+            })
+        }
+        _ => None,
+    };
+    // When there are no expectations for method with default implementation,
+    // call default implementation instead of failing.
+    // Destructuring patterns are applied to synthesized arguments, so
+    // that default implementation sees original bindings.
+    let default_call = default_body.map(|body| {
+        let destructure = args
+            .iter()
+            .zip(arg_descs.iter())
+            .filter_map(|(arg, (ident, _))| match *arg {
+                FnArg::Captured(ArgCaptured { pat: Pat::Ident(..), .. }) => None,
+                FnArg::Captured(ArgCaptured { ref pat, .. }) => {
+                    Some(quote! { let #pat = #ident; })
+                }
+                _ => None,
+            });
+        quote! {
+            if !scenario.borrow().has_expectations_for(&method_data) {
+                #(#destructure)*
+                return #body;
+            }
+        }
+    });
+    // When there are no expectations for method, return default value
+    // instead of failing, if return type implements `Default`.
+    let default_return = if ctx.default_returns {
+        let result_type = boxed_impl_trait(return_type).unwrap_or_else(|| return_type.clone());
+        let result_expr = if is_async {
+            quote! { ::std::future::ready(result) }
+        } else {
+            quote! { result }
+        };
+        Some(quote! {
+            if !scenario.borrow().has_expectations_for(&method_data) {
+                if let ::std::option::Option::Some(result) = ::mockers::maybe_default::<#result_type>() {
+                    return #result_expr;
+                }
+            }
+        })
+    } else {
+        None
+    };
+    // Async stub returns future which is immediately ready with action result.
+    let (stub_return_type, result_expr) = if is_async {
+        (
+            quote! { impl ::std::future::Future<Output = #return_type> },
+            quote! { ::std::future::ready(action.call()) },
+        )
+    } else if let Some(boxed_type) = boxed_impl_trait(return_type) {
+        // Stub keeps `impl Trait` return type, action returns boxed value.
+        // Boxed value is `'static`, like one given to expectation, and it
+        // outlives lifetimes of `impl Trait + '_` anyway.
+        let method_lifetimes: Vec<Lifetime> =
+            generics.lifetimes().map(|l| l.lifetime.clone()).collect();
+        let boxed_type = make_lifetimes_static(&boxed_type, &method_lifetimes);
+        (
+            quote! { #return_type },
+            quote! {{
+                let result: #boxed_type = action.call();
+                result
+            }},
+        )
+    } else {
+        (quote! { #return_type }, quote! { action.call() })
+    };
     let type_ids_expr = gen_type_ids_expr(generics);
+    // `where` clause isn't printed as part of generics, so it must be added
+    // explicitly for stub signature to match original method.
+    let where_clause = &generics.where_clause;
+    // Variadic tail of foreign function is accepted, but ignored, only fixed
+    // arguments are verified. Defining variadic function requires `c_variadic`
+    // feature.
+    let variadic_arg = if is_variadic {
+        Some(quote! { _variadic_args: ... })
+    } else {
+        None
+    };
 
     Ok(quote! {
         #[allow(unused_mut)]
-        #unsafe_t fn #method_ident #generics (#(#impl_args),*) -> #return_type {
+        #no_mangle
+        #track_caller
+        #vis #unsafe_t #abi fn #method_ident #generics (#(#impl_args,)* #variadic_arg)
+            -> #stub_return_type #where_clause
+        {
             let (mock_id, scenario) = #get_info_expr;
             let method_data = ::mockers::MethodData { mock_id: mock_id,
                                                       mock_type_id: #mock_type_id,
                                                       method_name: #method_name,
                                                       type_param_ids: #type_ids_expr };
+            #delegate_call
+            #default_call
+            #default_return
             let action = scenario.borrow_mut().#verify_fn(method_data, #(#arg_values),*);
-            action.call()
+            #result_expr
         }
     })
 }
 
+/// Returns identifier and type of method argument. Identifier `arg<index>`
+/// is synthesized for arguments with patterns other than plain identifier,
+/// like `(x, y): (i32, i32)`.
+fn arg_ident_and_type(index: usize, arg: &FnArg) -> Result<(Ident, Type), Error> {
+    let (ident, ty) = match *arg {
+        FnArg::Captured(ArgCaptured {
+            pat: Pat::Ident(PatIdent { ref ident, .. }),
+            ref ty,
+            ..
+        }) => (ident.clone(), ty),
+        FnArg::Captured(ArgCaptured { ref ty, .. }) | FnArg::Ignored(ref ty) => {
+            (Ident::new(&format!("arg{}", index), Span::call_site()), ty)
+        }
+        _ => return Err(Error::new_spanned(arg, "unsupported argument")),
+    };
+
+    // There is no way to name type of `impl Trait` argument in matcher.
+    // `impl Display` is the only exception, it is erased to `String`.
+    let is_impl_trait = match *ty {
+        Type::ImplTrait(..) => erased_arg_type(ty).is_none(),
+        Type::Reference(TypeReference { ref elem, .. }) => matches!(**elem, Type::ImplTrait(..)),
+        _ => false,
+    };
+    if is_impl_trait {
+        return Err(Error::new_spanned(
+            ty,
+            "`impl Trait` arguments other than `impl Display` are not supported, use trait \
+             object (`Box<dyn Trait>`, `&dyn Trait`) or type parameter instead",
+        ));
+    }
+
+    Ok((ident, ty.clone()))
+}
+
 /// Generate mock implementation method for creating expectations.
 ///
 /// Returns `ItemImpl` for generated method or `None` in case of errors.
@@ -771,42 +1770,41 @@ fn generate_stub_code(
 /// ```ignore
 /// #[allow(dead_code)]
 /// pub fn bar_call<Arg0Match: ::mockers::MatchArg<u32>>(&self,
-///                                                      arg0: Arg0Match)
+///                                                      a: Arg0Match)
 ///  -> ::mockers::CallMatch1<u32, ()> {
 ///     ::mockers::CallMatch1::new(self.mock_id, 1usize /* mock_id */,
-///                                Box::new(arg0))
+///                                Box::new(a))
 /// }
 /// ```
 fn generate_impl_method_for_trait(
-    mock_type_id: usize,
-    method_ident: Ident,
-    generics: &Generics,
-    args: &Punctuated<FnArg, Token![,]>,
-    return_type: &Type,
+    ctx: &MethodsContext,
+    method: &MockedMethod,
     trait_path: &Path,
-) -> Result<TokenStream, String> {
+    assoc_types: &[(Ident, Ident)],
+    attrs: &[&Attribute],
+) -> Result<TokenStream, Error> {
     // Types of arguments and result may refer to `Self`, which is ambiguos in the
     // context of trait implementation. All references to `Self` must be replaced
-    // with `<Self as Trait>`
-    let fixed_return_type = qualify_self(return_type, trait_path);
-    let fixed_args = Punctuated::from_iter(args.iter().map(|arg| match arg {
+    // with `<Self as Trait>`, except for associated types of trait, which are
+    // replaced with corresponding mock type parameters.
+    let fixed_return_type = qualify_self(method.return_type, trait_path, assoc_types);
+    let fixed_args = Punctuated::from_iter(method.args.iter().map(|arg| match arg {
         self_arg @ FnArg::SelfRef(..) => self_arg.clone(),
         self_arg @ FnArg::SelfValue(..) => self_arg.clone(),
         FnArg::Captured(ArgCaptured { pat, ty, .. }) => {
-            let qty = qualify_self(ty, trait_path);
+            let qty = qualify_self(ty, trait_path, assoc_types);
             parse_quote! { #pat: #qty }
         }
-        FnArg::Ignored(ty) => FnArg::Ignored(qualify_self(ty, trait_path)),
+        FnArg::Ignored(ty) => FnArg::Ignored(qualify_self(ty, trait_path, assoc_types)),
         FnArg::Inferred(pat) => FnArg::Inferred(pat.clone()),
     }));
 
-    generate_impl_method(
-        mock_type_id,
-        method_ident,
-        &generics,
-        &fixed_args,
-        &fixed_return_type,
-    )
+    let fixed_method = MockedMethod {
+        args: &fixed_args,
+        return_type: &fixed_return_type,
+        ..*method
+    };
+    generate_impl_method(ctx, &fixed_method, attrs)
 }
 
 /// Generate mock implementation method for creating expectations.
@@ -818,19 +1816,55 @@ fn generate_impl_method_for_trait(
 /// ```ignore
 /// #[allow(dead_code)]
 /// pub fn bar_call<Arg0Match: ::mockers::MatchArg<u32>>(&self,
-///                                                      arg0: Arg0Match)
+///                                                      a: Arg0Match)
 ///  -> ::mockers::CallMatch1<u32, ()> {
 ///     ::mockers::CallMatch1::new(self.mock_id, 1usize /* mock_id */,
-///                                Box::new(arg0))
+///                                Box::new(a))
+/// }
+/// ```
+///
+/// Returned `CallMatchN` is turned into expectation with reactions like
+/// `and_return`, or directly with `times` when result type implements `Default`.
+///
+/// Type parameters of generic method get `Debug` bound if `debug_bound` is `true`.
+///
+/// Besides `*_call` method, `expect_*` one is generated for fluent API,
+/// see `mockers::expect`:
+/// ```ignore
+/// #[allow(dead_code)]
+/// pub fn expect_bar(&self) -> ::mockers::Expect1<u32, ()> {
+///     ::mockers::Expect1::new(self.scenario.clone(), self.mock_id, 1usize, "bar", vec![])
 /// }
 /// ```
+/// Given attributes are put on both methods.
+///
+/// With `opaque_call_match`, `*_call` returns `impl ::mockers::CallMatchReactions<(u32,), ()>`
+/// instead of concrete `CallMatchN` type.
 fn generate_impl_method(
-    mock_type_id: usize,
-    method_ident: Ident,
-    generics: &Generics,
-    args: &Punctuated<FnArg, Token![,]>,
-    return_type: &Type,
-) -> Result<TokenStream, String> {
+    ctx: &MethodsContext,
+    method: &MockedMethod,
+    attrs: &[&Attribute],
+) -> Result<TokenStream, Error> {
+    let MockedMethod {
+        ident: method_ident,
+        name: method_name,
+        generics,
+        args,
+        return_type,
+        ..
+    } = *method;
+    let mock_type_id = ctx.mock_type_id;
+    check_args_count(method_ident, args)?;
+
+    // Defaults of method type parameters are rejected by compiler
+    // (`invalid_type_param_default` lint) and aren't used for inference.
+    if let Some(param) = generics.type_params().find(|p| p.default.is_some()) {
+        return Err(Error::new_spanned(
+            param,
+            "default type parameters of methods are not supported",
+        ));
+    }
+
     // For each argument generate...
     let mut arg_matcher_types = Vec::<TokenStream>::new();
     let mut inputs = Vec::<TokenStream>::new();
@@ -839,7 +1873,6 @@ fn generate_impl_method(
     let mut new_args = Vec::<TokenStream>::new();
     new_args.push(quote! { self.mock_id });
     new_args.push(quote! { #mock_type_id });
-    let method_name = method_ident.to_string();
     new_args.push(quote! { #method_name });
     new_args.push(gen_type_ids_expr(generics).into_token_stream());
 
@@ -847,17 +1880,19 @@ fn generate_impl_method(
     let mut arg_lifetimes = Vec::new();
     let mut new_arg_types = Vec::new();
 
+    // Generic methods accept matchers as `impl MatchArg<...>` instead of
+    // type parameters, so that only method type parameters have to be given
+    // explicitly, e.g. `mock.get_call::<u32>(ANY)`.
+    let is_generic = generics.type_params().next().is_some();
+
     for (i, arg) in args.iter().enumerate() {
-        let (_ident, arg_type) = match *arg {
-            FnArg::Captured(ArgCaptured {
-                pat: Pat::Ident(PatIdent { ref ident, .. }),
-                ref ty,
-                ..
-            }) => (ident.clone(), ty.clone()),
-            _ => unreachable!(),
-        };
+        // Original argument names are used, so that editors show meaningful hints.
+        let (arg_ident, arg_type) = arg_ident_and_type(i, arg)?;
+        // Besides matchers, owned values are accepted for `&u32`-like arguments
+        // and string literals are accepted for erased `impl Display` ones.
+        let into_match_arg = accepts_owned_value(&arg_type) || erased_arg_type(&arg_type).is_some();
+        let arg_type = erased_arg_type(&arg_type).unwrap_or(arg_type);
         let arg_type_ident = Ident::new(&format!("Arg{}Match", i), Span::call_site());
-        let arg_ident = Ident::new(&format!("arg{}", i), Span::call_site());
 
         // To support reference parameters we must create lifetime parameter for each of them
         // and modify parameter type to adopt new lifetime.
@@ -865,9 +1900,13 @@ fn generate_impl_method(
         //
         // ```ignore
         // pub fn foo_call<'a0, Arg0Match: ::mockers::MatchArg<&'a0 u32> + 'static>
-        //                (&self, arg0: Arg0Match)
+        //                (&self, a: Arg0Match)
         //  -> ::mockers::CallMatch1<&'a0 u32, ()>;
         // ```
+        //
+        // Elided lifetimes nested in parameter type, like `Cow<'_, str>` or
+        // `Option<&str>`, are given names the same way.
+        let mut nested_lifetimes = Vec::new();
         let new_arg_type = match &arg_type {
             // Parameter is reference
             &Type::Reference(TypeReference {
@@ -877,57 +1916,152 @@ fn generate_impl_method(
             }) => {
                 // Create separate lifetime.
                 let lifetime = Lifetime::new(&format!("'a{}", i), Span::call_site());
-                let lifetime = quote! { #lifetime };
-                arg_lifetimes.push(lifetime.clone());
+                let ty = name_elided_lifetimes(ty, i, &mut nested_lifetimes);
+                arg_lifetimes.push(quote! { #lifetime });
                 quote! { &#lifetime #mutability #ty }
             }
 
             // Parameter is not reference
-            _ => quote! { #arg_type },
+            _ => {
+                let ty = name_elided_lifetimes(&arg_type, i, &mut nested_lifetimes);
+                quote! { #ty }
+            }
         };
+        arg_lifetimes.extend(nested_lifetimes.iter().map(|l| quote! { #l }));
         new_arg_types.push(new_arg_type.clone());
 
         // 1. Type parameter
-        let match_arg_path = quote! { ::mockers::MatchArg<#new_arg_type>};
-        arg_matcher_types.push(quote! { #arg_type_ident: #match_arg_path + 'static });
-        inputs.push(quote! { #arg_ident: #arg_type_ident });
+        let match_arg_path = if into_match_arg {
+            quote! { ::mockers::IntoMatchArg<#new_arg_type>}
+        } else {
+            quote! { ::mockers::MatchArg<#new_arg_type>}
+        };
+        if is_generic {
+            inputs.push(quote! { #arg_ident: impl #match_arg_path + 'static });
+        } else {
+            arg_matcher_types.push(quote! { #arg_type_ident: #match_arg_path + 'static });
+            inputs.push(quote! { #arg_ident: #arg_type_ident });
+        }
 
-        new_args.push(quote! { Box::new(#arg_ident) });
+        if into_match_arg {
+            new_args.push(quote! { ::mockers::IntoMatchArg::into_match_arg(#arg_ident) });
+        } else {
+            new_args.push(quote! { ::std::boxed::Box::new(#arg_ident) });
+        }
     }
 
     let call_match_ident = Ident::new(&format!("CallMatch{}", args.len()), Span::call_site());
 
+    // Returned references borrow from `self` or from arguments, but value
+    // for them is given by user before call, so it must outlive call.
+    let method_lifetimes: Vec<Lifetime> =
+        generics.lifetimes().map(|l| l.lifetime.clone()).collect();
+    check_impl_trait_lifetimes(return_type, &method_lifetimes)?;
+    let return_type = boxed_impl_trait(return_type).unwrap_or_else(|| return_type.clone());
+    let return_type = make_lifetimes_static(&return_type, &method_lifetimes);
+
+    let new_arg_types_ref = &new_arg_types;
+    let reactions_ret_type = quote! {
+        impl ::mockers::CallMatchReactions<(#(#new_arg_types_ref,)*), #return_type>
+    };
     let mut call_match_args: Vec<_> = new_arg_types;
     call_match_args.push(quote! { #return_type });
+    let call_match_args = &call_match_args;
     let ret_type = quote! { ::mockers::#call_match_ident<#(#call_match_args),*> };
+    let expect_ident = Ident::new(&format!("Expect{}", args.len()), Span::call_site());
+    let expect_ret_type = quote! { ::mockers::#expect_ident<#(#call_match_args),*> };
+
+    let output = if ctx.opaque_call_match {
+        reactions_ret_type
+    } else {
+        ret_type.clone()
+    };
+    // Raw identifiers like `r#match` give `match_call`, which isn't keyword.
+    let expect_method_name =
+        Ident::new(&format!("{}_call", unraw(method_ident)), Span::call_site());
 
-    let output = ret_type.clone();
-    let expect_method_name = Ident::new(&format!("{}_call", method_ident), Span::call_site());
+    let fluent_method_name =
+        Ident::new(&format!("expect_{}", unraw(method_ident)), Span::call_site());
 
     let debug_param_bound: TypeParamBound = syn::parse_str("::std::fmt::Debug").unwrap();
-    let generic_params = [
+    let method_generic_params = [
         &arg_lifetimes[..],
         &generics
             .params
             .iter()
             .flat_map(|p| match p {
+                // Method lifetimes may be used by argument types, e.g. `Option<&'a str>`.
+                GenericParam::Lifetime(p) => Some(quote! { #p }),
                 GenericParam::Type(p) => {
                     let mut p = p.clone();
-                    p.bounds.push(debug_param_bound.clone());
+                    if ctx.debug_bound {
+                        p.bounds.push(debug_param_bound.clone());
+                    }
                     Some(quote! { #p })
                 }
                 _ => None,
             })
             .collect::<Vec<_>>()[..],
-        &arg_matcher_types[..],
     ]
     .concat();
+    let generic_params = [&method_generic_params[..], &arg_matcher_types[..]].concat();
+
+    // Method `where` clause is kept, except for predicates on `Self`, which
+    // would refer to mock type.
+    let where_predicates: Vec<&WherePredicate> = generics
+        .where_clause
+        .iter()
+        .flat_map(|w| w.predicates.iter())
+        .filter(|p| !is_self_predicate(p))
+        .collect();
+    let where_clause = if where_predicates.is_empty() {
+        None
+    } else {
+        let where_predicates = &where_predicates;
+        Some(quote! { where #(#where_predicates),* })
+    };
+    // `CallMatchReactions` is implemented for `'static` argument types only,
+    // so it can't be returned for arbitrary lifetimes. Expectation requires
+    // `'static` arguments anyway.
+    let call_where_clause = if ctx.opaque_call_match {
+        let predicates: Vec<TokenStream> = where_predicates
+            .iter()
+            .map(|p| quote! { #p })
+            .chain(arg_lifetimes.iter().map(|l| quote! { #l: 'static }))
+            .chain(method_lifetimes.iter().map(|l| quote! { #l: 'static }))
+            .collect();
+        if predicates.is_empty() {
+            None
+        } else {
+            Some(quote! { where #(#predicates),* })
+        }
+    } else {
+        where_clause.clone()
+    };
+
+    // Matchers are given to `with` method of returned `ExpectN`.
+    let expect_new_args = &new_args[..4];
+    let new_args = &new_args;
 
     let impl_subitem: TokenStream = quote! {
+        #(#attrs)*
         #[allow(dead_code)]
-        pub fn #expect_method_name<#(#generic_params),*>(&self, #(#inputs),*) -> #output {
+        pub fn #expect_method_name<#(#generic_params),*>(&self, #(#inputs),*) -> #output
+            #call_where_clause
+        {
             ::mockers::#call_match_ident::new(#(#new_args),*)
         }
+
+        #(#attrs)*
+        #[allow(dead_code)]
+        pub fn #fluent_method_name<#(#method_generic_params),*>(&self) -> #expect_ret_type
+            #where_clause
+        {
+            ::mockers::#expect_ident::new(
+                ::std::clone::Clone::clone(&self.scenario),
+                #(#expect_new_args),*
+            )
+        }
     };
 
     Ok(impl_subitem)
@@ -936,12 +2070,12 @@ fn generate_impl_method(
 fn generate_extern_mock(
     foreign_mod: &syn::ItemForeignMod,
     mock_ident: &Ident,
-) -> Result<TokenStream, String> {
-    let mock_type_id = unsafe {
-        let id = NEXT_MOCK_TYPE_ID;
-        NEXT_MOCK_TYPE_ID += 1;
-        id
-    };
+    teardown: Option<&Path>,
+) -> Result<TokenStream, Error> {
+    let mock_type_key = format!("{}#extern", mock_ident);
+    let mock_type_id = mock_type_id_expr(&mock_type_key);
+    let ctx = MethodsContext::for_functions(&mock_type_id);
+    let stub_abi = unwinding_abi(&foreign_mod.abi);
 
     let (mock_items, stub_items): (Vec<_>, Vec<_>) = foreign_mod
         .items
@@ -956,60 +2090,226 @@ fn generate_extern_mock(
                     ReturnType::Type(_, ref ty) => *ty.clone(),
                     ReturnType::Default => parse_quote! { () },
                 };
-                let mock_method = generate_impl_method(
-                    mock_type_id,
-                    ident.clone(),
-                    &decl.generics,
-                    &decl.inputs,
-                    &ret_ty,
-                )?;
-
-                let get_info_expr = quote! {
-                    ::mockers::EXTERN_MOCKS.with(|mocks| {
-                        mocks.borrow().get(&#mock_type_id).expect("Mock instance not found").clone()
-                    })
-                };
-                let stub_method = generate_stub_code(
-                    mock_type_id,
+                let method_name = unraw(ident);
+                let method = MockedMethod {
                     ident,
-                    &decl.generics,
-                    None,
-                    get_info_expr,
-                    &decl.inputs,
-                    &ret_ty,
-                    true,
-                )?;
+                    name: &method_name,
+                    generics: &decl.generics,
+                    args: &decl.inputs,
+                    return_type: &ret_ty,
+                    is_async: false,
+                };
+                let mock_method = generate_impl_method(&ctx, &method, &[])?;
+
+                let get_info_expr = quote! { ::mockers::get_extern_mock(#mock_type_id) };
+                let stub_opts = StubOptions {
+                    is_unsafe: true,
+                    abi: Some(&stub_abi),
+                    is_variadic: decl.variadic.is_some(),
+                    ..Default::default()
+                };
+                let stub_method =
+                    generate_stub_code(&ctx, &method, None, get_info_expr, stub_opts)?;
 
                 Ok((mock_method, stub_method))
             }
 
-            ForeignItem::Static(..) => return Err("extern statics are not supported".to_string()),
-            ForeignItem::Type(..) => return Err("types are not supported".to_string()),
-            ForeignItem::Macro(..) => return Err("macros are not supported".to_string()),
-            ForeignItem::Verbatim(..) => return Err("verbatim items are not supported".to_string()),
+            ForeignItem::Static(ForeignItemStatic {
+                mutability: Some(ref mutability),
+                ..
+            }) => Err(Error::new_spanned(mutability, "mutable statics are not supported")),
+            ForeignItem::Static(ForeignItemStatic {
+                ref ident,
+                ref ty,
+                ..
+            }) => {
+                // Value of static is kept in thread-local storage, like mocks
+                // registry, so that parallel tests don't interfere. Static is
+                // replaced with unsafe function of the same name returning the
+                // value, it isn't exported because it isn't a static anymore.
+                let value_ident = static_value_ident(ident);
+                let setter_ident = Ident::new(&format!("set_{}", unraw(ident)), Span::call_site());
+                let not_set_msg = format!("value of extern static `{}` isn't set", unraw(ident));
+                let mock_method = quote! {
+                    pub fn #setter_ident(&self, value: #ty) {
+                        #value_ident.with(|v| *v.borrow_mut() = ::std::option::Option::Some(value));
+                    }
+                };
+                let stub_static = quote! {
+                    ::std::thread_local! {
+                        static #value_ident: ::std::cell::RefCell<::std::option::Option<#ty>> =
+                            ::std::cell::RefCell::new(::std::option::Option::None);
+                    }
+                    pub unsafe fn #ident() -> #ty {
+                        #value_ident.with(|v| {
+                            ::std::clone::Clone::clone(v.borrow().as_ref().expect(#not_set_msg))
+                        })
+                    }
+                };
+                Ok((mock_method, stub_static))
+            }
+            ForeignItem::Type(..) => Err(Error::new_spanned(item, "types are not supported")),
+            ForeignItem::Macro(..) => Err(Error::new_spanned(item, "macros are not supported")),
+            ForeignItem::Verbatim(..) => {
+                Err(Error::new_spanned(item, "verbatim items are not supported"))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .unzip();
+
+    // Values of statics are cleared when mock is dropped, so they don't leak
+    // into following tests.
+    let static_value_idents = foreign_mod.items.iter().filter_map(|item| match item {
+        ForeignItem::Static(ForeignItemStatic { ref ident, .. }) => Some(static_value_ident(ident)),
+        _ => None,
+    });
+    let teardown_call = teardown.map(|teardown| quote! { #teardown(); });
+    let drop_code = quote! {
+        #(#static_value_idents.with(|v| *v.borrow_mut() = ::std::option::Option::None);)*
+        #teardown_call
+    };
+
+    let vis: Visibility = parse_quote! { pub };
+    Ok(generate_registered_mock(
+        &vis,
+        mock_ident,
+        &mock_type_key,
+        "extern block",
+        &mock_items,
+        &stub_items,
+        &drop_code,
+    ))
+}
+
+/// Returns ABI of stubs replacing functions of foreign module. Mock reports
+/// unexpected call by panicking, and panic can't unwind out of `extern "C"`
+/// function, it aborts the whole test binary instead. So stubs use unwinding
+/// variant of ABI, like `"C-unwind"` for `"C"`, which is compatible with it.
+fn unwinding_abi(abi: &Abi) -> Abi {
+    const UNWINDING_ABIS: &[&str] = &[
+        "C", "cdecl", "stdcall", "fastcall", "vectorcall", "thiscall", "aapcs", "win64",
+        "sysv64", "system",
+    ];
+    // `extern { ... }` means `extern "C" { ... }`.
+    let name = abi.name.as_ref().map_or_else(|| "C".to_owned(), |name| name.value());
+    if !UNWINDING_ABIS.contains(&name.as_str()) {
+        return abi.clone();
+    }
+    Abi {
+        extern_token: abi.extern_token,
+        name: Some(LitStr::new(&format!("{}-unwind", name), Span::call_site())),
+    }
+}
+
+/// Returns name of thread-local variable keeping value of mocked extern static.
+fn static_value_ident(ident: &Ident) -> Ident {
+    Ident::new(
+        &format!("MOCKERS_{}_VALUE", unraw(ident).to_uppercase()),
+        Span::call_site(),
+    )
+}
+
+/// Generate mock for free functions given to `mock_mod!`. Functions are
+/// replaced with stubs having the same signatures, which may be imported
+/// instead of real ones. Like extern block mocks, only one mock may exist
+/// at a time.
+fn generate_mod_mock(
+    vis: &Visibility,
+    mock_ident: &Ident,
+    fns: &[ForeignItemFn],
+) -> Result<TokenStream, Error> {
+    let mock_type_key = format!("{}#mod", mock_ident);
+    let mock_type_id = mock_type_id_expr(&mock_type_key);
+    let ctx = MethodsContext::for_functions(&mock_type_id);
+
+    let (mock_items, stub_items): (Vec<_>, Vec<_>) = fns
+        .iter()
+        .map(|func| {
+            let ForeignItemFn {
+                ref decl,
+                ref ident,
+                vis: ref fn_vis,
+                ..
+            } = *func;
+            if let Some(ref variadic) = decl.variadic {
+                return Err(Error::new_spanned(
+                    variadic,
+                    "variadic functions are supported in extern blocks only",
+                ));
+            }
+            let ret_ty = match decl.output {
+                ReturnType::Type(_, ref ty) => *ty.clone(),
+                ReturnType::Default => parse_quote! { () },
+            };
+            let method_name = unraw(ident);
+            let method = MockedMethod {
+                ident,
+                name: &method_name,
+                generics: &decl.generics,
+                args: &decl.inputs,
+                return_type: &ret_ty,
+                is_async: false,
+            };
+            let mock_method = generate_impl_method(&ctx, &method, &[])?;
+
+            let get_info_expr = quote! { ::mockers::get_extern_mock(#mock_type_id) };
+            let stub_opts = StubOptions {
+                vis: Some(fn_vis),
+                ..Default::default()
+            };
+            let stub_fn = generate_stub_code(&ctx, &method, None, get_info_expr, stub_opts)?;
+
+            Ok((mock_method, stub_fn))
         })
         .collect::<Result<Vec<_>, _>>()?
         .into_iter()
         .unzip();
 
+    Ok(generate_registered_mock(
+        vis,
+        mock_ident,
+        &mock_type_key,
+        "module",
+        &mock_items,
+        &stub_items,
+        &quote! {},
+    ))
+}
+
+/// Generate mock struct registered in thread-local registry of `mockers`
+/// on creation, so that stubs may find it by mock type ID. Used for
+/// extern blocks and free functions, which have no `self` to get mock from.
+/// `mock_type_key` is given to `mock_type_id_expr`. `drop_code` is run when mock is dropped, after unregistering, e.g. to
+/// call teardown function.
+fn generate_registered_mock(
+    vis: &Visibility,
+    mock_ident: &Ident,
+    mock_type_key: &str,
+    mocked_what: &str,
+    mock_items: &[TokenStream],
+    stub_items: &[TokenStream],
+    drop_code: &TokenStream,
+) -> TokenStream {
     let mock_class_name = mock_ident.to_string();
+    let already_exists_msg = format!("Mock {{}} for {} already exists", mocked_what);
+    let mock_type_id = mock_type_id_expr(mock_type_key);
+    let mock_type_name = mock_type_name_expr(mock_type_key);
 
     let mock_struct = quote! {
-        pub struct #mock_ident {
+        #vis struct #mock_ident {
+            scenario: ::std::rc::Rc<::std::cell::RefCell<::mockers::ScenarioInternals>>,
             mock_id: usize,
         }
     };
     let mock_impl = quote! {
         impl ::mockers::Mock for #mock_ident {
             fn new(id: usize, scenario_int: ::std::rc::Rc<::std::cell::RefCell<::mockers::ScenarioInternals>>) -> Self {
-                ::mockers::EXTERN_MOCKS.with(|mocks| {
-                    let mut mocks = mocks.borrow_mut();
-                    if mocks.contains_key(&#mock_type_id) {
-                        panic!("Mock {} for extern block already exists", #mock_class_name);
-                    }
-                    mocks.insert(#mock_type_id, (id, scenario_int.clone()));
-                });
+                if !::mockers::register_extern_mock(#mock_type_id, #mock_type_name, id, &scenario_int) {
+                    panic!(#already_exists_msg, #mock_class_name);
+                }
                 #mock_ident {
+                    scenario: scenario_int,
                     mock_id: id,
                 }
             }
@@ -1020,21 +2320,150 @@ fn generate_extern_mock(
         }
     };
 
-    Ok(quote! {
+    quote! {
         #mock_struct
         #mock_impl
-        impl Drop for #mock_ident {
+        impl ::std::ops::Drop for #mock_ident {
             fn drop(&mut self) {
-                ::mockers::EXTERN_MOCKS.with(|mocks| {
-                    let mut mocks = mocks.borrow_mut();
-                    mocks.remove(&#mock_type_id);
-                });
+                ::mockers::unregister_extern_mock(#mock_type_id, self.mock_id, &self.scenario);
+                #drop_code
             }
         }
         impl #mock_ident {
             #(#mock_items)*
         }
         #(#stub_items)*
+    }
+}
+
+/// Generate mock for inherent impl block.
+/// Mock struct gets methods with the same signatures as original ones,
+/// which verify calls against scenario, plus usual `*_call` methods for
+/// creating expectations. Original impl block is left untouched.
+fn generate_impl_mock(item_impl: &ItemImpl, opts: &MockAttrOptions) -> Result<TokenStream, Error> {
+    if let Some((_, ref trait_path, _)) = item_impl.trait_ {
+        return Err(Error::new_spanned(
+            trait_path,
+            "Attribute may be used on inherent impl blocks only, mock trait itself instead",
+        ));
+    }
+    if !item_impl.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &item_impl.generics,
+            "Generic impl blocks are not supported yet",
+        ));
+    }
+    let self_ident = match *item_impl.self_ty {
+        Type::Path(TypePath {
+            qself: None,
+            ref path,
+        }) => path.segments.iter().last().unwrap().ident.clone(),
+        ref ty => {
+            return Err(Error::new_spanned(ty, "Only impl blocks for named types are supported"));
+        }
+    };
+    let mock_ident = default_mock_ident(opts, &self_ident.to_string());
+    let vis: Visibility = opts.vis.clone().unwrap_or_else(|| parse_quote! { pub });
+    let mock_type_id = mock_type_id_expr(&format!("{}#impl", mock_ident));
+    let ctx = MethodsContext {
+        mock_type_id: &mock_type_id,
+        debug_bound: !opts.no_debug_bound,
+        default_returns: opts.default_returns,
+        opaque_call_match: opts.opaque_call_match,
+    };
+
+    let mut stub_methods = Vec::new();
+    let mut impl_methods = Vec::new();
+    for impl_item in &item_impl.items {
+        let method = match impl_item {
+            ImplItem::Method(ref method) => method,
+            _ => {
+                return Err(Error::new_spanned(
+                    impl_item,
+                    "Only methods are supported in mocked impl blocks",
+                ));
+            }
+        };
+        let sig = &method.sig;
+        if let Some(ref unsafety) = sig.unsafety {
+            return Err(Error::new_spanned(unsafety, "unsafe methods are not supported"));
+        }
+        if let Some(ref abi) = sig.abi {
+            return Err(Error::new_spanned(abi, "non-Rust ABIs for methods are not supported"));
+        }
+        let self_arg = match sig.decl.inputs.iter().next() {
+            Some(arg) if is_self_arg(arg) => arg,
+            _ => {
+                return Err(Error::new_spanned(
+                    &sig.ident,
+                    "Methods without `self` are not supported in mocked impl blocks yet",
+                ));
+            }
+        };
+        let args = Punctuated::from_iter(sig.decl.inputs.iter().skip(1).cloned());
+
+        let return_type = match sig.decl.output {
+            ReturnType::Default => parse_quote! { () },
+            ReturnType::Type(_, ref ty) => *ty.clone(),
+        };
+        let (return_type, is_async) = if sig.asyncness.is_some() {
+            (return_type, true)
+        } else {
+            match future_output_type(&return_type) {
+                Some(output_type) => (output_type, true),
+                None => (return_type, false),
+            }
+        };
+
+        // `Self` in signatures refers to mock struct in generated methods,
+        // just like it refers to original type in original ones.
+        let method_name = unraw(&sig.ident);
+        let mocked_method = MockedMethod {
+            ident: &sig.ident,
+            name: &method_name,
+            generics: &sig.decl.generics,
+            args: &args,
+            return_type: &return_type,
+            is_async,
+        };
+        let stub_opts = StubOptions {
+            vis: Some(&method.vis),
+            ..Default::default()
+        };
+        stub_methods.push(generate_trait_impl_method(&ctx, &mocked_method, self_arg, stub_opts)?);
+        let doc_attrs: Vec<&Attribute> = method.attrs.iter().filter(|a| is_doc_attr(a)).collect();
+        impl_methods.push(generate_impl_method(&ctx, &mocked_method, &doc_attrs)?);
+    }
+
+    let mocked_class_name = self_ident.to_string();
+    let struct_item = generate_mock_struct(
+        &vis,
+        &mock_ident,
+        &MockGenerics::default(),
+        &opts.derives,
+        None,
+    );
+    let test_only_guard = test_only_guard(opts, &mock_ident.to_string());
+    let mock_impl_item = generate_mock_impl(
+        &mock_ident,
+        &mocked_class_name,
+        &MockGenerics::default(),
+        &test_only_guard,
+        None,
+    );
+
+    Ok(quote! {
+        #struct_item
+        #mock_impl_item
+        impl ::std::fmt::Debug for #mock_ident {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str(self.scenario.borrow().get_mock_name(self.mock_id))
+            }
+        }
+        impl #mock_ident {
+            #(#stub_methods)*
+            #(#impl_methods)*
+        }
     })
 }
 
@@ -1081,6 +2510,11 @@ where
                 ref qself,
                 ref path,
             }) => {
+                // `Self` may be used in qualified path too, e.g. `<Self as Trait>::Item`.
+                let qself = qself.as_ref().map(|qself| QSelf {
+                    ty: Box::new(process_ty(&qself.ty, func)),
+                    ..qself.clone()
+                });
                 if qself.is_none()
                     && path
                         .segments
@@ -1095,13 +2529,19 @@ where
                     )
                 } else {
                     Type::Path(TypePath {
-                        qself: qself.clone(),
+                        qself,
                         path: process_path(&path, func),
                     })
                 }
             }
-            t @ Type::TraitObject(..) => t.clone(),
-            Type::ImplTrait(ref bounds) => Type::ImplTrait(bounds.clone()),
+            Type::TraitObject(ref t) => Type::TraitObject(TypeTraitObject {
+                bounds: process_bounds(&t.bounds, func),
+                ..t.clone()
+            }),
+            Type::ImplTrait(ref t) => Type::ImplTrait(TypeImplTrait {
+                bounds: process_bounds(&t.bounds, func),
+                ..t.clone()
+            }),
             Type::Paren(ref t) => Type::Paren(TypeParen {
                 elem: Box::new(process_ty(&t.elem, func)),
                 paren_token: syn::token::Paren(Span::call_site()),
@@ -1115,6 +2555,24 @@ where
             v @ Type::Verbatim(..) => v.clone(),
         }
     }
+    fn process_bounds<Func>(
+        bounds: &Punctuated<TypeParamBound, Token![+]>,
+        func: &Func,
+    ) -> Punctuated<TypeParamBound, Token![+]>
+    where
+        Func: Fn(&syn::PathSegment, &[syn::PathSegment]) -> Type,
+    {
+        bounds
+            .iter()
+            .map(|bound| match bound {
+                TypeParamBound::Trait(ref trait_bound) => TypeParamBound::Trait(TraitBound {
+                    path: process_path(&trait_bound.path, func),
+                    ..trait_bound.clone()
+                }),
+                TypeParamBound::Lifetime(..) => bound.clone(),
+            })
+            .collect()
+    }
     fn process_bare_fn_arg<Func>(arg: &BareFnArg, func: &Func) -> BareFnArg
     where
         Func: Fn(&syn::PathSegment, &[syn::PathSegment]) -> Type,
@@ -1186,47 +2644,487 @@ where
     process_ty(&ty, &func)
 }
 
+/// Returns type which argument of given type is converted to before passing
+/// to scenario, if any. `impl Display` arguments are converted to `String`,
+/// so that they may be matched like `log_call("message")`.
+fn erased_arg_type(ty: &Type) -> Option<Type> {
+    let bounds = match *ty {
+        Type::ImplTrait(TypeImplTrait { ref bounds, .. }) => bounds,
+        _ => return None,
+    };
+    let mut trait_bounds = bounds.iter().filter_map(|bound| match *bound {
+        TypeParamBound::Trait(ref bound) => Some(bound),
+        TypeParamBound::Lifetime(..) => None,
+    });
+    match (trait_bounds.next(), trait_bounds.next()) {
+        (Some(bound), None) => {
+            let last = bound.path.segments.iter().last()?;
+            if last.ident == "Display" && last.arguments.is_empty() {
+                Some(parse_quote! { ::std::string::String })
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns identifier name without `r#` prefix of raw identifiers, so that
+/// it may be used in messages and as part of other identifiers.
+fn unraw(ident: &Ident) -> String {
+    let name = ident.to_string();
+    name.strip_prefix("r#").map(str::to_owned).unwrap_or(name)
+}
+
+/// Returns whether argument is method receiver: `self`, `&self` or `self`
+/// with explicit type, like `self: Box<Self>` or `self: Pin<&mut Self>`.
+fn is_self_arg(arg: &FnArg) -> bool {
+    match *arg {
+        FnArg::SelfRef(..) | FnArg::SelfValue(..) => true,
+        FnArg::Captured(ArgCaptured {
+            pat: Pat::Ident(PatIdent { ref ident, .. }),
+            ..
+        }) => ident == "self",
+        _ => false,
+    }
+}
+
+/// Checks that method doesn't have more arguments than supported by `CallMatchN`
+/// types, so that user gets clear error instead of unresolved type name.
+fn check_args_count(method_ident: &Ident, args: &Punctuated<FnArg, Token![,]>) -> Result<(), Error> {
+    if args.len() > MAX_ARGS_COUNT {
+        return Err(Error::new_spanned(
+            method_ident,
+            format!(
+                "method `{}` has {} arguments, but mocked methods may have at most {}",
+                unraw(method_ident),
+                args.len(),
+                MAX_ARGS_COUNT
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns whether `*_call` method accepts owned value for argument of given
+/// type, i.e. whether it is shared reference to primitive type, `String` or
+/// `str`. It is done here rather than with blanket `MatchArg` impls, which would
+/// make type parameters of generic methods ambiguous.
+fn accepts_owned_value(ty: &Type) -> bool {
+    const OWNED_VALUE_TYPES: &[&str] = &[
+        "bool", "char", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64",
+        "u128", "usize", "f32", "f64", "String", "str",
+    ];
+    let elem = match *ty {
+        Type::Reference(TypeReference {
+            mutability: None,
+            ref elem,
+            ..
+        }) => elem,
+        _ => return false,
+    };
+    match **elem {
+        Type::Path(TypePath { qself: None, ref path }) => {
+            path.leading_colon.is_none()
+                && path.segments.len() == 1
+                && path.segments[0].arguments.is_empty()
+                && OWNED_VALUE_TYPES.iter().any(|name| path.segments[0].ident == name)
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether attribute is `#[cfg(...)]`.
+fn is_cfg_attr(attr: &Attribute) -> bool {
+    attr.path.segments.len() == 1 && attr.path.segments[0].ident == "cfg"
+}
+
+/// Adds given `#[cfg(...)]` attributes to each generated item, so that mock
+/// is compiled under the same conditions as mocked trait.
+fn add_cfg_attrs(tokens: TokenStream, cfg_attrs: &[Attribute]) -> Result<TokenStream, Error> {
+    if cfg_attrs.is_empty() {
+        return Ok(tokens);
+    }
+
+    let mut file: syn::File = syn::parse2(tokens)?;
+    for item in &mut file.items {
+        let attrs = match item {
+            Item::ExternCrate(item) => &mut item.attrs,
+            Item::Use(item) => &mut item.attrs,
+            Item::Static(item) => &mut item.attrs,
+            Item::Const(item) => &mut item.attrs,
+            Item::Fn(item) => &mut item.attrs,
+            Item::Mod(item) => &mut item.attrs,
+            Item::ForeignMod(item) => &mut item.attrs,
+            Item::Type(item) => &mut item.attrs,
+            Item::Existential(item) => &mut item.attrs,
+            Item::Struct(item) => &mut item.attrs,
+            Item::Enum(item) => &mut item.attrs,
+            Item::Union(item) => &mut item.attrs,
+            Item::Trait(item) => &mut item.attrs,
+            Item::TraitAlias(item) => &mut item.attrs,
+            Item::Impl(item) => &mut item.attrs,
+            Item::Macro(item) => &mut item.attrs,
+            Item::Macro2(item) => &mut item.attrs,
+            Item::Verbatim(item) => {
+                return Err(Error::new_spanned(&item.tts, "unexpected item in generated mock"))
+            }
+        };
+        attrs.splice(0..0, cfg_attrs.iter().cloned());
+    }
+    Ok(file.into_token_stream())
+}
+
+/// Returns whether attribute is one of attributes requested with `keep_attrs`
+/// option. `#[must_use]` is never kept: compiler already applies it through
+/// trait method declaration and warns about it on trait implementation methods.
+fn is_kept_attr(attr: &Attribute, keep_attrs: &[Ident]) -> bool {
+    attr.path.segments.len() == 1
+        && attr.path.segments[0].ident != "must_use"
+        && keep_attrs.contains(&attr.path.segments[0].ident)
+}
+
+/// Returns whether attribute is `#[doc = "..."]`, i.e. doc comment.
+fn is_doc_attr(attr: &Attribute) -> bool {
+    attr.path.segments.len() == 1 && attr.path.segments[0].ident == "doc"
+}
+
+/// Returns whether `where` clause predicate bounds `Self` type, like `Self: Sized`.
+fn is_self_predicate(predicate: &WherePredicate) -> bool {
+    match predicate {
+        WherePredicate::Type(PredicateType {
+            bounded_ty: Type::Path(TypePath { qself: None, ref path }),
+            ..
+        }) => path.segments.len() == 1 && path.segments[0].ident == "Self",
+        _ => false,
+    }
+}
+
+/// Replaces elided lifetimes and given lifetimes in type with `'static`.
+fn make_lifetimes_static(ty: &Type, lifetimes: &[Lifetime]) -> Type {
+    let is_replaced = |l: &Lifetime| l.ident == "_" || lifetimes.contains(l);
+    match ty {
+        Type::Reference(ref r) => Type::Reference(TypeReference {
+            lifetime: match r.lifetime {
+                Some(ref l) if !is_replaced(l) => Some(l.clone()),
+                _ => Some(parse_quote! { 'static }),
+            },
+            elem: Box::new(make_lifetimes_static(&r.elem, lifetimes)),
+            ..r.clone()
+        }),
+        Type::Path(ref p) => {
+            let mut p = p.clone();
+            make_path_lifetimes_static(&mut p.path, lifetimes);
+            Type::Path(p)
+        }
+        // Boxed `impl Trait + '_` results, like `Box<dyn Iterator<Item = u32> + '_>`.
+        Type::TraitObject(ref t) => {
+            let mut t = t.clone();
+            for bound in t.bounds.iter_mut() {
+                match *bound {
+                    TypeParamBound::Trait(ref mut b) => {
+                        make_path_lifetimes_static(&mut b.path, lifetimes);
+                    }
+                    TypeParamBound::Lifetime(ref mut l) if is_replaced(l) => {
+                        *l = parse_quote! { 'static };
+                    }
+                    _ => {}
+                }
+            }
+            Type::TraitObject(t)
+        }
+        Type::Tuple(ref t) => Type::Tuple(TypeTuple {
+            elems: t.elems.iter().map(|e| make_lifetimes_static(e, lifetimes)).collect(),
+            ..t.clone()
+        }),
+        Type::Slice(ref t) => Type::Slice(TypeSlice {
+            elem: Box::new(make_lifetimes_static(&t.elem, lifetimes)),
+            ..t.clone()
+        }),
+        Type::Array(ref t) => Type::Array(TypeArray {
+            elem: Box::new(make_lifetimes_static(&t.elem, lifetimes)),
+            ..t.clone()
+        }),
+        Type::Paren(ref t) => Type::Paren(TypeParen {
+            elem: Box::new(make_lifetimes_static(&t.elem, lifetimes)),
+            ..t.clone()
+        }),
+        _ => ty.clone(),
+    }
+}
+
+/// Replaces elided lifetimes in type of argument with given index, either
+/// anonymous `'_` ones or omitted lifetimes of references, with fresh named
+/// lifetimes like `'a0_0`, which are added to `lifetimes`.
+///
+/// Lifetimes omitted from paths, like in `Cow<str>`, can't be detected
+/// without knowing type definition, so they are left as is.
+fn name_elided_lifetimes(ty: &Type, arg_index: usize, lifetimes: &mut Vec<Lifetime>) -> Type {
+    let fresh_lifetime = |lifetimes: &mut Vec<Lifetime>| {
+        let lifetime = Lifetime::new(
+            &format!("'a{}_{}", arg_index, lifetimes.len()),
+            Span::call_site(),
+        );
+        lifetimes.push(lifetime.clone());
+        lifetime
+    };
+    match ty {
+        Type::Reference(ref r) => Type::Reference(TypeReference {
+            lifetime: match r.lifetime {
+                Some(ref l) if l.ident != "_" => Some(l.clone()),
+                _ => Some(fresh_lifetime(lifetimes)),
+            },
+            elem: Box::new(name_elided_lifetimes(&r.elem, arg_index, lifetimes)),
+            ..r.clone()
+        }),
+        Type::Path(ref p) => {
+            let mut p = p.clone();
+            for seg in p.path.segments.iter_mut() {
+                if let PathArguments::AngleBracketed(ref mut args) = seg.arguments {
+                    for arg in args.args.iter_mut() {
+                        match *arg {
+                            GenericArgument::Type(ref mut t) => {
+                                *t = name_elided_lifetimes(t, arg_index, lifetimes);
+                            }
+                            GenericArgument::Binding(ref mut b) => {
+                                b.ty = name_elided_lifetimes(&b.ty, arg_index, lifetimes);
+                            }
+                            GenericArgument::Lifetime(ref mut l) if l.ident == "_" => {
+                                *l = fresh_lifetime(lifetimes);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            Type::Path(p)
+        }
+        Type::Tuple(ref t) => Type::Tuple(TypeTuple {
+            elems: t.elems.iter().map(|e| name_elided_lifetimes(e, arg_index, lifetimes)).collect(),
+            ..t.clone()
+        }),
+        Type::Slice(ref t) => Type::Slice(TypeSlice {
+            elem: Box::new(name_elided_lifetimes(&t.elem, arg_index, lifetimes)),
+            ..t.clone()
+        }),
+        Type::Array(ref t) => Type::Array(TypeArray {
+            elem: Box::new(name_elided_lifetimes(&t.elem, arg_index, lifetimes)),
+            ..t.clone()
+        }),
+        Type::Paren(ref t) => Type::Paren(TypeParen {
+            elem: Box::new(name_elided_lifetimes(&t.elem, arg_index, lifetimes)),
+            ..t.clone()
+        }),
+        _ => ty.clone(),
+    }
+}
+
+/// Replaces elided lifetimes and given lifetimes in path generic arguments,
+/// including associated type bindings like `Item = &'a T`, with `'static`.
+fn make_path_lifetimes_static(path: &mut Path, lifetimes: &[Lifetime]) {
+    let is_replaced = |l: &Lifetime| l.ident == "_" || lifetimes.contains(l);
+    for seg in path.segments.iter_mut() {
+        if let PathArguments::AngleBracketed(ref mut args) = seg.arguments {
+            for arg in args.args.iter_mut() {
+                match *arg {
+                    GenericArgument::Type(ref mut t) => {
+                        *t = make_lifetimes_static(t, lifetimes);
+                    }
+                    GenericArgument::Binding(ref mut b) => {
+                        b.ty = make_lifetimes_static(&b.ty, lifetimes);
+                    }
+                    GenericArgument::Lifetime(ref mut l) if is_replaced(l) => {
+                        *l = parse_quote! { 'static };
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Checks that trait bounds of returned `impl Trait` don't use method
+/// lifetimes, like `impl Iterator<Item = &'a str>`. Boxed result given
+/// to expectation is `'static`, and `dyn Iterator<Item = &'static str>`
+/// isn't `impl Iterator<Item = &'a str>`, because bindings are invariant.
+/// Lifetime bound itself, like `+ 'a`, is fine.
+fn check_impl_trait_lifetimes(ty: &Type, lifetimes: &[Lifetime]) -> Result<(), Error> {
+    let bounds = match ty {
+        Type::ImplTrait(TypeImplTrait { ref bounds, .. }) => bounds,
+        _ => return Ok(()),
+    };
+    for bound in bounds {
+        if let TypeParamBound::Trait(TraitBound { ref path, .. }) = bound {
+            let mut static_path = path.clone();
+            make_path_lifetimes_static(&mut static_path, lifetimes);
+            if static_path != *path {
+                return Err(Error::new_spanned(
+                    bound,
+                    "returned `impl Trait` can't use method lifetimes in its type arguments, \
+                     because value given to expectation is `'static`",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether arguments (except for receiver) or result of method
+/// refer to `Self` type itself, like `fn merge(&self, other: &Self)`.
+/// Such methods make trait not object safe unless bound by `Self: Sized`.
+/// References to associated types, like `Self::Item`, are fine.
+fn mentions_self_in_signature(decl: &FnDecl) -> bool {
+    let mentions_self = |ty: &Type| {
+        let found = Cell::new(false);
+        replace_self(ty, |self_seg: &syn::PathSegment, rest: &[syn::PathSegment]| {
+            if rest.is_empty() {
+                found.set(true);
+            }
+            parse_quote! { #self_seg }
+        });
+        found.get()
+    };
+    let in_args = decl.inputs.iter().filter(|arg| !is_self_arg(arg)).any(|arg| match *arg {
+        FnArg::Captured(ArgCaptured { ref ty, .. }) | FnArg::Ignored(ref ty) => mentions_self(ty),
+        _ => false,
+    });
+    let in_result = match decl.output {
+        ReturnType::Type(_, ref ty) => mentions_self(ty),
+        ReturnType::Default => false,
+    };
+    in_args || in_result
+}
+
+/// Returns whether function return type is `impl Trait`.
+fn returns_impl_trait(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, ref ty) => matches!(**ty, Type::ImplTrait(..)),
+        ReturnType::Default => false,
+    }
+}
+
+/// If `ty` is `impl Trait`, returns `Box<dyn Trait>`. It is used as result
+/// type of expectations, because `impl Trait` type can't be named.
+fn boxed_impl_trait(ty: &Type) -> Option<Type> {
+    match ty {
+        Type::ImplTrait(TypeImplTrait { ref bounds, .. }) => {
+            Some(parse_quote! { ::std::boxed::Box<dyn #bounds> })
+        }
+        _ => None,
+    }
+}
+
+/// If `ty` is `impl Future<Output = T>`, returns `T`.
+fn future_output_type(ty: &Type) -> Option<Type> {
+    let bounds = match ty {
+        Type::ImplTrait(TypeImplTrait { ref bounds, .. }) => bounds,
+        _ => return None,
+    };
+    for bound in bounds {
+        let path = match bound {
+            TypeParamBound::Trait(TraitBound { ref path, .. }) => path,
+            TypeParamBound::Lifetime(..) => continue,
+        };
+        let segment = match path.segments.last() {
+            Some(segment) => segment.into_value(),
+            None => continue,
+        };
+        if segment.ident != "Future" {
+            continue;
+        }
+        if let PathArguments::AngleBracketed(ref data) = segment.arguments {
+            for arg in &data.args {
+                if let GenericArgument::Binding(Binding { ref ident, ref ty, .. }) = arg {
+                    if ident == "Output" {
+                        return Some(ty.clone());
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
 /// Replace all unqualified references to `Self` with qualified ones.
-fn qualify_self(ty: &Type, trait_path: &Path) -> Type {
+/// Bare `Self` (like in `Result<Self, E>`) is unambiguous and left as is.
+/// Associated types from `assoc_types` are replaced with corresponding
+/// mock type parameters, e.g. `Self::Item` becomes `Item`.
+fn qualify_self(ty: &Type, trait_path: &Path, assoc_types: &[(Ident, Ident)]) -> Type {
     replace_self(
         ty,
         |self_seg: &syn::PathSegment, rest: &[syn::PathSegment]| {
-            let self_ty = parse_quote! { #self_seg };
-            let new_qself = QSelf {
-                as_token: Some(Token![as](Span::call_site())),
-                gt_token: Token![>](Span::call_site()),
-                lt_token: Token![<](Span::call_site()),
-                ty: Box::new(self_ty),
-                position: trait_path.segments.len(),
-            };
-            let mut new_segments = trait_path.segments.clone();
-            new_segments.extend(rest.iter().cloned());
-            Type::Path(TypePath {
-                qself: Some(new_qself),
-                path: Path {
-                    leading_colon: None,
-                    segments: new_segments,
-                },
+            assoc_type_param(rest, assoc_types).unwrap_or_else(|| {
+                qualify_type(parse_quote! { #self_seg }, trait_path, rest)
             })
         },
     )
 }
 
-/// Replace all references to `Self` with given type reference.
-fn set_self(ty: &Type, mock_struct_path: &Path) -> Type {
+/// Replace all references to `Self` with given type reference. Associated
+/// types from `assoc_types` are replaced with corresponding mock type
+/// parameters, other ones are accessed through trait, e.g. `Self::Item`
+/// becomes `<AMock<Item> as A>::Item`.
+fn set_self(
+    ty: &Type,
+    mock_struct_path: &Path,
+    trait_path: &Path,
+    assoc_types: &[(Ident, Ident)],
+) -> Type {
     replace_self(
         ty,
         |_self_seg: &syn::PathSegment, rest: &[syn::PathSegment]| {
-            let mut new_segments = mock_struct_path.segments.clone();
-            new_segments.extend(rest.iter().cloned());
-            parse_quote! { #new_segments }
+            assoc_type_param(rest, assoc_types).unwrap_or_else(|| {
+                qualify_type(parse_quote! { #mock_struct_path }, trait_path, rest)
+            })
         },
     )
 }
 
-pub fn mock_impl(input: TokenStream) -> Result<TokenStream, String> {
-    let args = parse_macro_args(input).map_err(|_| "can't parse macro input".to_string())?;
-    let tokens = generate_mock_for_traits(args.ident, &args.traits, false)?;
+/// Returns mock type parameter corresponding to `Self::rest` if it is
+/// associated type from `assoc_types`.
+fn assoc_type_param(rest: &[syn::PathSegment], assoc_types: &[(Ident, Ident)]) -> Option<Type> {
+    match rest {
+        [segment] if segment.arguments.is_empty() => assoc_types
+            .iter()
+            .find(|(assoc, _)| *assoc == segment.ident)
+            .map(|(_, param)| parse_quote! { #param }),
+        _ => None,
+    }
+}
+
+/// Returns `<self_ty as Trait>::rest` type, or just `self_ty` if `rest` is empty.
+fn qualify_type(self_ty: Type, trait_path: &Path, rest: &[syn::PathSegment]) -> Type {
+    if rest.is_empty() {
+        return self_ty;
+    }
+    let new_qself = QSelf {
+        as_token: Some(Token![as](Span::call_site())),
+        gt_token: Token![>](Span::call_site()),
+        lt_token: Token![<](Span::call_site()),
+        ty: Box::new(self_ty),
+        position: trait_path.segments.len(),
+    };
+    let mut new_segments = trait_path.segments.clone();
+    new_segments.extend(rest.iter().cloned());
+    Type::Path(TypePath {
+        qself: Some(new_qself),
+        path: Path {
+            leading_colon: None,
+            segments: new_segments,
+        },
+    })
+}
+
+pub fn mock_impl(input: TokenStream) -> Result<TokenStream, Error> {
+    let args = parse_macro_args(input)?;
+    let opts = MockAttrOptions {
+        vis: match args.vis {
+            Visibility::Inherited => None,
+            vis => Some(vis),
+        },
+        ..MockAttrOptions::default()
+    };
+    let tokens = generate_mock_for_traits(args.ident, &args.traits, false, args.extend, &opts)?;
 
     if cfg!(feature = "debug") {
         eprintln!("{}", tokens.to_string());
@@ -1235,14 +3133,57 @@ pub fn mock_impl(input: TokenStream) -> Result<TokenStream, String> {
     Ok(tokens)
 }
 
+pub fn mock_mod_impl(input: TokenStream) -> Result<TokenStream, Error> {
+    let args = parse_mock_mod_args(input)?;
+    let vis = match args.vis {
+        Visibility::Inherited => parse_quote! { pub },
+        vis => vis,
+    };
+    let tokens = generate_mod_mock(&vis, &args.ident, &args.fns)?;
+
+    if cfg!(feature = "debug") {
+        eprintln!("{}", tokens);
+    }
+
+    Ok(tokens)
+}
+
 /// Given generic params, returns expression returning vector of type parameter IDs.
+///
+/// IDs of `'static` type parameters are derived from `TypeId`, other types must
+/// be registered with `register_types!`.
 fn gen_type_ids_expr(generics: &Generics) -> Expr {
     let type_param_id_exprs = generics.params.iter().flat_map(|g| {
         match g {
+            GenericParam::Type(TypeParam{ref ident, ..}) if has_static_bound(generics, ident) =>
+                Some(quote!(::mockers::type_info::static_type_id::<#ident>())),
             GenericParam::Type(TypeParam{ref ident, ..}) =>
-                Some(quote!(<MockersTypeRegistry<#ident> as ::mockers::TypeInfo>::get_type_id())),
+                Some(quote!(::mockers::type_info::registered_type_id::<MockersTypeRegistry<#ident>>())),
             _ => None,
         }
     });
-    parse_quote!(vec![#(#type_param_id_exprs),*])
+    parse_quote!(::std::vec![#(#type_param_id_exprs),*])
+}
+
+/// Returns whether type parameter is bound by `'static` lifetime, either
+/// in parameter list or in `where` clause.
+fn has_static_bound(generics: &Generics, ident: &Ident) -> bool {
+    let is_static = |bound: &TypeParamBound| match bound {
+        TypeParamBound::Lifetime(lifetime) => lifetime.ident == "static",
+        _ => false,
+    };
+
+    let in_params = generics.type_params().any(|param| {
+        param.ident == *ident && param.bounds.iter().any(&is_static)
+    });
+    let param_type: Type = parse_quote!(#ident);
+    let in_where_clause = generics.where_clause.as_ref().is_some_and(|where_clause| {
+        where_clause.predicates.iter().any(|predicate| match predicate {
+            WherePredicate::Type(PredicateType { bounded_ty, bounds, .. }) => {
+                *bounded_ty == param_type && bounds.iter().any(&is_static)
+            }
+            _ => false,
+        })
+    });
+    in_params || in_where_clause
 }
@@ -11,18 +11,18 @@ use proc_macro::TokenStream;
 mod codegen;
 mod options;
 
-use crate::codegen::{mock_impl, mocked_impl, register_types_impl};
+use crate::codegen::{mock_impl, mock_mod_impl, mocked_impl, register_trait_impl, register_types_impl};
 use crate::options::parse_attr_options;
 
 #[proc_macro_attribute]
 pub fn mocked(attr: TokenStream, input: TokenStream) -> TokenStream {
     let opts = match parse_attr_options(attr.into()) {
         Ok(opts) => opts,
-        Err(err) => panic!("{}", err),
+        Err(err) => return err.to_compile_error().into(),
     };
     match mocked_impl(input.into(), &opts) {
         Ok(tokens) => tokens,
-        Err(err) => panic!("{}", err),
+        Err(err) => err.to_compile_error(),
     }
     .into()
 }
@@ -40,7 +40,16 @@ pub fn derive_mock(attr: TokenStream, input: TokenStream) -> TokenStream {
 pub fn mock(input: TokenStream) -> TokenStream {
     match mock_impl(input.into()) {
         Ok(tokens) => tokens,
-        Err(err) => panic!("{}", err),
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn mock_mod(input: TokenStream) -> TokenStream {
+    match mock_mod_impl(input.into()) {
+        Ok(tokens) => tokens,
+        Err(err) => err.to_compile_error(),
     }
     .into()
 }
@@ -49,7 +58,16 @@ pub fn mock(input: TokenStream) -> TokenStream {
 pub fn register_types(input: TokenStream) -> TokenStream {
     match register_types_impl(input.into()) {
         Ok(tokens) => tokens,
-        Err(err) => panic!("{}", err),
+        Err(err) => err.to_compile_error(),
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn register_trait(input: TokenStream) -> TokenStream {
+    match register_trait_impl(input.into()) {
+        Ok(tokens) => tokens,
+        Err(err) => err.to_compile_error(),
     }
     .into()
 }
@@ -3,8 +3,8 @@ use std::collections::HashMap;
 
 use proc_macro2::{Span, TokenStream};
 use syn::{
-    parse::ParseStream, punctuated::Punctuated, Ident, ItemTrait, Meta, MetaNameValue, NestedMeta,
-    Path, Token,
+    parse::ParseStream, punctuated::Punctuated, ForeignItem, ForeignItemFn, Ident, ItemTrait, Meta,
+    MetaList, MetaNameValue, NestedMeta, Path, PathSegment, Token, Visibility,
 };
 
 pub fn parse_attr_options(attr_tokens: TokenStream) -> syn::parse::Result<MockAttrOptions> {
@@ -15,17 +15,107 @@ pub fn parse_macro_args(tokens: TokenStream) -> syn::parse::Result<MockMacroArgs
     syn::parse2::<MockMacroArgs>(tokens)
 }
 
+pub fn parse_mock_mod_args(tokens: TokenStream) -> syn::parse::Result<MockModArgs> {
+    syn::parse2::<MockModArgs>(tokens)
+}
+
+#[derive(Default)]
 pub struct MockAttrOptions {
     pub mock_name: Option<Ident>,
+    /// Pattern of mock struct name used when `mock_name` isn't set, `{}`
+    /// is replaced with name of mocked type. `{}Mock` is used when not set.
+    pub mock_name_pattern: Option<String>,
     pub module_path: Option<Path>,
     pub refs: HashMap<Path, Path>,
+    /// Module aliases given as `refs` glob entries (`io::* => ::app::io::*`),
+    /// any known trait under target module may be referenced.
+    pub ref_modules: Vec<(Path, Path)>,
+    /// Don't add `Debug` bound to mock type parameters.
+    pub no_debug_bound: bool,
+    /// Call default implementation of trait method when there are
+    /// no expectations for it.
+    pub delegate_defaults: bool,
+    /// Return default value from methods which have no expectations,
+    /// when their return type implements `Default`.
+    pub default_returns: bool,
+    /// Visibility of generated mock struct, `pub` is used when not set.
+    pub vis: Option<Visibility>,
+    /// Name of mock struct for static methods, mock name with `Static`
+    /// suffix is used when not set.
+    pub static_mock_name: Option<Ident>,
+    /// Put `*_call` methods for static methods to main mock instead of
+    /// separate static mock.
+    pub no_static_mock: bool,
+    /// Implement `Clone` for mock, clones share expectations.
+    pub clone: bool,
+    /// Implement `Send` and `Sync` for mock. It is unsound, because mock
+    /// shares non-thread-safe scenario state, so user must guarantee that
+    /// mock and scenario are never used from several threads simultaneously.
+    pub unsafe_send_sync: bool,
+    /// Methods which aren't mocked, default implementation from trait
+    /// is used for them.
+    pub skip: Vec<Ident>,
+    /// Additional derives for mock struct.
+    pub derives: Vec<Ident>,
+    /// Generate `spy` constructor accepting real implementation of trait,
+    /// calls of methods having no expectations are delegated to it.
+    pub spy: bool,
+    /// Names of attributes which are copied from trait methods to mock's
+    /// trait implementation methods.
+    pub keep_attrs: Vec<Ident>,
+    /// Panic on mock creation when crate isn't compiled for tests.
+    pub test_only: bool,
+    /// Function called when extern block mock is dropped, for cleaning up
+    /// state which mocked functions set.
+    pub teardown: Option<Path>,
+    /// Return `impl CallMatchReactions` from `*_call` methods instead
+    /// of concrete `CallMatchN` types.
+    pub opaque_call_match: bool,
+}
+
+/// Parses `refs` entry path which may end with `::*` glob, sole `*` stands
+/// for glob with empty module path. Returns path without glob and whether
+/// glob was present.
+fn parse_ref_path(stream: ParseStream<'_>) -> syn::parse::Result<(Path, bool)> {
+    let leading_colon = stream.parse::<Option<Token![::]>>()?;
+    let mut segments = Punctuated::<PathSegment, Token![::]>::new();
+    while !stream.peek(Token![*]) {
+        segments.push_value(stream.parse::<PathSegment>()?);
+        if !stream.peek(Token![::]) {
+            return Ok((Path { leading_colon, segments }, false));
+        }
+        let colon = stream.parse::<Token![::]>()?;
+        if stream.peek(Token![*]) {
+            break;
+        }
+        segments.push_punct(colon);
+    }
+    stream.parse::<Token![*]>()?;
+    Ok((Path { leading_colon, segments }, true))
 }
 
 impl syn::parse::Parse for MockAttrOptions {
     fn parse(input: ParseStream<'_>) -> syn::parse::Result<Self> {
         let mut mock_name: Option<Ident> = None;
+        let mut mock_name_pattern: Option<String> = None;
         let mut module_path: Option<Path> = None;
         let mut refs: HashMap<Path, Path> = HashMap::new();
+        let mut ref_modules: Vec<(Path, Path)> = Vec::new();
+        let mut no_debug_bound = false;
+        let mut delegate_defaults = false;
+        let mut default_returns = false;
+        let mut vis: Option<Visibility> = None;
+        let mut static_mock_name: Option<Ident> = None;
+        let mut no_static_mock = false;
+        let mut clone = false;
+        let mut unsafe_send_sync = false;
+        let mut skip: Vec<Ident> = Vec::new();
+        let mut derives: Vec<Ident> = Vec::new();
+        let mut spy = false;
+        let mut keep_attrs: Vec<Ident> = Vec::new();
+        let mut test_only = false;
+        let mut teardown: Option<Path> = None;
+        let mut opaque_call_match = false;
 
         let metas = input.parse_terminated::<NestedMeta, Token![,]>(NestedMeta::parse)?;
         if metas.is_empty() {
@@ -42,29 +132,41 @@ impl syn::parse::Parse for MockAttrOptions {
                         use syn::parse::Parser;
 
                         let parser = |stream: ParseStream<'_>| {
-                            stream.parse_terminated::<(Path, Path), Token![,]>(|stream| {
-                                let source = stream.parse::<Path>()?;
+                            stream.parse_terminated::<_, Token![,]>(|stream| {
+                                let source = parse_ref_path(stream)?;
                                 stream.parse::<Token![=>]>()?;
-                                let target = stream.parse::<Path>()?;
+                                let target = parse_ref_path(stream)?;
                                 Ok((source, target))
                             })
                         };
                         let refs_list = parser.parse_str(&refs_lit.value())?;
 
-                        for (source, target) in refs_list {
-                            if source.leading_colon.is_some() {
-                                return Err(syn::Error::new(
-                                    Span::call_site(),
-                                    "global source path".to_string(),
+                        for ((source, source_glob), (target, target_glob)) in refs_list {
+                            if source_glob != target_glob {
+                                return Err(syn::Error::new_spanned(
+                                    &item,
+                                    "glob must be used on both sides of refs entry".to_string(),
+                                ));
+                            }
+                            // Exact entries may map global path of re-exported
+                            // trait to the one it is registered under.
+                            if source_glob && source.leading_colon.is_some() {
+                                return Err(syn::Error::new_spanned(
+                                    &item,
+                                    "global source path in glob entry".to_string(),
                                 ));
                             }
                             if target.leading_colon.is_none() {
-                                return Err(syn::Error::new(
-                                    Span::call_site(),
+                                return Err(syn::Error::new_spanned(
+                                    &item,
                                     "local target path".to_string(),
                                 ));
                             }
-                            refs.insert(source, target);
+                            if source_glob {
+                                ref_modules.push((source, target));
+                            } else {
+                                refs.insert(source, target);
+                            }
                         }
                     }
 
@@ -74,38 +176,255 @@ impl syn::parse::Parse for MockAttrOptions {
                         ..
                     })) if name == "module" => {
                         if module_path.is_some() {
-                            return Err(syn::Error::new(
-                                Span::call_site(),
+                            return Err(syn::Error::new_spanned(
+                                &item,
                                 "module attribute parameters is used more than once".to_string(),
                             ));
                         }
                         let path: Path = syn::parse_str(&path_lit.value())?;
                         if path.leading_colon.is_none() {
-                            return Err(syn::Error::new(
-                                Span::call_site(),
+                            return Err(syn::Error::new_spanned(
+                                &item,
                                 "module path must be global".to_string(),
                             ));
                         }
                         module_path = Some(path);
                     }
 
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        ident: ref name,
+                        lit: syn::Lit::Str(ref vis_lit),
+                        ..
+                    })) if name == "vis" => {
+                        if vis.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                &item,
+                                "vis attribute parameters is used more than once".to_string(),
+                            ));
+                        }
+                        vis = Some(syn::parse_str(&vis_lit.value())?);
+                    }
+
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        ident: ref name,
+                        lit: syn::Lit::Str(ref name_lit),
+                        ..
+                    })) if name == "static_mock_name" => {
+                        if static_mock_name.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                &item,
+                                "static_mock_name attribute parameters is used more than once"
+                                    .to_string(),
+                            ));
+                        }
+                        static_mock_name = Some(syn::parse_str(&name_lit.value())?);
+                    }
+
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        ident: ref name,
+                        lit: syn::Lit::Str(ref pattern_lit),
+                        ..
+                    })) if name == "mock_name_pattern" => {
+                        if mock_name_pattern.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                &item,
+                                "mock_name_pattern attribute parameters is used more than once"
+                                    .to_string(),
+                            ));
+                        }
+                        let pattern = pattern_lit.value();
+                        if pattern.matches("{}").count() != 1 {
+                            return Err(syn::Error::new_spanned(
+                                pattern_lit,
+                                "mock_name_pattern must contain exactly one `{}` placeholder",
+                            ));
+                        }
+                        // Check that pattern produces valid identifier.
+                        syn::parse_str::<Ident>(&pattern.replace("{}", "A")).map_err(|_| {
+                            syn::Error::new_spanned(
+                                pattern_lit,
+                                "mock_name_pattern must produce valid identifier",
+                            )
+                        })?;
+                        mock_name_pattern = Some(pattern);
+                    }
+
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        ident: ref name,
+                        lit: syn::Lit::Str(ref path_lit),
+                        ..
+                    })) if name == "teardown" => {
+                        if teardown.is_some() {
+                            return Err(syn::Error::new_spanned(
+                                &item,
+                                "teardown attribute parameters is used more than once".to_string(),
+                            ));
+                        }
+                        teardown = Some(syn::parse_str(&path_lit.value())?);
+                    }
+
+                    NestedMeta::Meta(Meta::List(MetaList {
+                        ident: ref name,
+                        ref nested,
+                        ..
+                    })) if name == "skip" => {
+                        for method in nested {
+                            match *method {
+                                NestedMeta::Meta(Meta::Word(ref ident)) => {
+                                    skip.push(ident.clone());
+                                }
+                                _ => {
+                                    return Err(syn::Error::new_spanned(
+                                        method,
+                                        "method name expected".to_string(),
+                                    ));
+                                }
+                            }
+                        }
+                    }
+
+                    NestedMeta::Meta(Meta::List(MetaList {
+                        ident: ref name,
+                        ref nested,
+                        ..
+                    })) if name == "derive" => {
+                        for derive in nested {
+                            match *derive {
+                                NestedMeta::Meta(Meta::Word(ref ident)) => {
+                                    derives.push(ident.clone());
+                                }
+                                _ => {
+                                    return Err(syn::Error::new_spanned(
+                                        derive,
+                                        "derive name expected".to_string(),
+                                    ));
+                                }
+                            }
+                        }
+                    }
+
+                    NestedMeta::Meta(Meta::List(MetaList {
+                        ident: ref name,
+                        ref nested,
+                        ..
+                    })) if name == "keep_attrs" => {
+                        for attr in nested {
+                            match *attr {
+                                NestedMeta::Meta(Meta::Word(ref ident)) => {
+                                    keep_attrs.push(ident.clone());
+                                }
+                                _ => {
+                                    return Err(syn::Error::new_spanned(
+                                        attr,
+                                        "attribute name expected".to_string(),
+                                    ));
+                                }
+                            }
+                        }
+                    }
+
+                    NestedMeta::Meta(Meta::Word(ref ident)) if ident == "no_debug_bound" => {
+                        no_debug_bound = true;
+                    }
+
+                    NestedMeta::Meta(Meta::Word(ref ident)) if ident == "delegate_defaults" => {
+                        delegate_defaults = true;
+                    }
+
+                    NestedMeta::Meta(Meta::Word(ref ident)) if ident == "default_returns" => {
+                        default_returns = true;
+                    }
+
+                    NestedMeta::Meta(Meta::Word(ref ident)) if ident == "no_static_mock" => {
+                        no_static_mock = true;
+                    }
+
+                    NestedMeta::Meta(Meta::Word(ref ident)) if ident == "clone" => {
+                        clone = true;
+                    }
+
+                    NestedMeta::Meta(Meta::Word(ref ident)) if ident == "unsafe_send_sync" => {
+                        unsafe_send_sync = true;
+                    }
+
+                    NestedMeta::Meta(Meta::Word(ref ident)) if ident == "spy" => {
+                        spy = true;
+                    }
+
+                    NestedMeta::Meta(Meta::Word(ref ident)) if ident == "test_only" => {
+                        test_only = true;
+                    }
+
+                    NestedMeta::Meta(Meta::Word(ref ident)) if ident == "opaque_call_match" => {
+                        opaque_call_match = true;
+                    }
+
                     NestedMeta::Meta(Meta::Word(ref ident)) => {
                         mock_name = Some(ident.clone());
                     }
 
                     _ => {
-                        return Err(syn::Error::new(
-                            Span::call_site(),
+                        return Err(syn::Error::new_spanned(
+                            &item,
                             "unexpected attribute parameter".to_string(),
                         ));
                     }
                 }
             }
         }
+        if no_static_mock && static_mock_name.is_some() {
+            return Err(syn::Error::new_spanned(
+                &static_mock_name,
+                "static_mock_name can't be used together with no_static_mock",
+            ));
+        }
+        if mock_name.is_some() && mock_name_pattern.is_some() {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "mock_name_pattern can't be used together with explicit mock name",
+            ));
+        }
+        if spy && clone {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "spy can't be used together with clone, delegate can't be cloned",
+            ));
+        }
+        if let Some(derive) = derives.iter().find(|d| *d == "Clone") {
+            if spy {
+                return Err(syn::Error::new_spanned(
+                    derive,
+                    "spy can't be used together with derive(Clone), delegate can't be cloned",
+                ));
+            }
+            if clone {
+                return Err(syn::Error::new_spanned(
+                    derive,
+                    "clone can't be used together with derive(Clone), mock is already cloneable",
+                ));
+            }
+        }
         Ok(MockAttrOptions {
             mock_name,
+            mock_name_pattern,
             module_path,
             refs,
+            ref_modules,
+            no_debug_bound,
+            delegate_defaults,
+            default_returns,
+            vis,
+            static_mock_name,
+            no_static_mock,
+            clone,
+            unsafe_send_sync,
+            skip,
+            derives,
+            spy,
+            keep_attrs,
+            test_only,
+            teardown,
+            opaque_call_match,
         })
     }
 }
@@ -136,18 +455,73 @@ impl syn::parse::Parse for TraitDesc {
 }
 
 pub struct MockMacroArgs {
+    /// `extend` form, traits are implemented for mock struct generated
+    /// by previous `mock!` invocation.
+    pub extend: bool,
+    pub vis: Visibility,
     pub ident: Ident,
     pub traits: Vec<TraitDesc>,
 }
 
 impl syn::parse::Parse for MockMacroArgs {
     fn parse(input: syn::parse::ParseStream<'_>) -> syn::parse::Result<Self> {
+        // `extend AMock, ...`, but not mock named `extend`.
+        let fork = input.fork();
+        let extend = match fork.parse::<Ident>() {
+            Ok(ref ident) => ident == "extend" && fork.peek(Ident),
+            Err(..) => false,
+        };
+        if extend {
+            input.parse::<Ident>()?;
+        }
+        let vis = input.parse::<Visibility>()?;
+        if extend && vis != Visibility::Inherited {
+            return Err(syn::Error::new_spanned(
+                &vis,
+                "visibility can't be given when extending mock".to_string(),
+            ));
+        }
         let ident = input.parse::<Ident>()?;
         input.parse::<Token![,]>()?;
         let traits: Punctuated<TraitDesc, Token![,]> = input.parse_terminated(TraitDesc::parse)?;
         Ok(MockMacroArgs {
-            ident: ident,
+            extend,
+            vis,
+            ident,
             traits: traits.into_iter().collect(),
         })
     }
 }
+
+/// Arguments of `mock_mod!`: mock name followed by signatures
+/// of free functions, like `FsMock, pub fn read(path: &str) -> String;`.
+pub struct MockModArgs {
+    pub vis: Visibility,
+    pub ident: Ident,
+    pub fns: Vec<ForeignItemFn>,
+}
+
+impl syn::parse::Parse for MockModArgs {
+    fn parse(input: syn::parse::ParseStream<'_>) -> syn::parse::Result<Self> {
+        let vis = input.parse::<Visibility>()?;
+        let ident = input.parse::<Ident>()?;
+        input.parse::<Token![,]>()?;
+        let mut fns = Vec::new();
+        while !input.is_empty() {
+            match input.parse::<ForeignItem>()? {
+                ForeignItem::Fn(func) => fns.push(func),
+                item => {
+                    return Err(syn::Error::new_spanned(
+                        &item,
+                        "function signature expected".to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(MockModArgs {
+            vis,
+            ident,
+            fns,
+        })
+    }
+}